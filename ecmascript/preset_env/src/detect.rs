@@ -0,0 +1,473 @@
+use crate::Feature;
+use fxhash::FxHashSet;
+use swc_atoms::{js_word, JsWord};
+use swc_common::{Visit, VisitWith};
+use swc_ecma_ast::*;
+
+/// Syntax features used by a program.
+#[derive(Debug, Default, Clone)]
+pub struct FeatureSet {
+    /// Features which can be lowered by [preset_env](crate::preset_env).
+    pub features: FxHashSet<Feature>,
+
+    /// `a?.b`
+    ///
+    /// This is a proposal, so it is not a [Feature].
+    pub optional_chaining: bool,
+
+    /// `class A { a = 1; static b = 2; }`
+    ///
+    /// This is a proposal, so it is not a [Feature].
+    pub class_properties: bool,
+
+    /// `class A { #a = 1; }`
+    ///
+    /// This is a proposal, so it is not a [Feature].
+    pub private_properties: bool,
+}
+
+impl FeatureSet {
+    pub fn contains(&self, f: Feature) -> bool {
+        self.features.contains(&f)
+    }
+}
+
+/// Reports syntax features used by `program`.
+///
+/// This only looks at the grammar. Usage of builtins (`Promise`,
+/// `Array.prototype.includes`, ...) is handled by the core-js usage visitors.
+pub fn detect_features(program: &Program) -> FeatureSet {
+    let mut v = FeatureDetector::default();
+    program.visit_with(&mut v);
+
+    // `typeof` is an es5 operator. It needs to be transformed only if a symbol
+    // can be passed to it.
+    if v.has_typeof && v.uses_symbol {
+        v.add(Feature::TypeOfSymbol);
+    }
+
+    v.found
+}
+
+#[derive(Default)]
+struct FeatureDetector {
+    found: FeatureSet,
+    has_typeof: bool,
+    /// True if `Symbol` is referenced.
+    uses_symbol: bool,
+    /// True while visiting an object literal, but not a class nested in it.
+    in_object_lit: bool,
+}
+
+impl FeatureDetector {
+    fn add(&mut self, f: Feature) {
+        self.found.features.insert(f);
+    }
+
+    fn check_params(&mut self, params: &[Pat]) {
+        let is_simple = params.iter().all(|p| match *p {
+            Pat::Ident(..) => true,
+            _ => false,
+        });
+
+        if !is_simple {
+            self.add(Feature::Parameters);
+        }
+    }
+
+    fn check_regex(&mut self, exp: &str, flags: &str) {
+        if flags.contains('y') {
+            self.add(Feature::StickyRegex);
+        }
+        if flags.contains('s') {
+            self.add(Feature::DotAllRegex);
+        }
+        if flags.contains('u') {
+            self.add(Feature::UnicodeRegex);
+
+            if exp.contains("\\p{") || exp.contains("\\P{") {
+                self.add(Feature::UnicodePropertyRegex);
+            }
+        }
+
+        // `(?<name>` but not `(?<=` or `(?<!`
+        let has_named_group =
+            exp.match_indices("(?<")
+                .any(|(idx, _)| match exp[idx + 3..].chars().next() {
+                    Some('=') | Some('!') | None => false,
+                    _ => true,
+                });
+        if has_named_group {
+            self.add(Feature::NamedCapturingGroupsRegex);
+        }
+    }
+}
+
+impl Visit<Expr> for FeatureDetector {
+    fn visit(&mut self, e: &Expr) {
+        e.visit_children(self);
+
+        match *e {
+            Expr::Tpl(..) | Expr::TaggedTpl(..) => self.add(Feature::TemplateLiterals),
+            Expr::Arrow(..) => self.add(Feature::ArrowFunctions),
+            Expr::OptChain(..) => self.found.optional_chaining = true,
+            Expr::MetaProp(MetaPropExpr {
+                ref meta, ref prop, ..
+            }) if meta.sym == js_word!("new") && prop.sym == js_word!("target") => {
+                self.add(Feature::NewTarget)
+            }
+            Expr::Unary(UnaryExpr {
+                op: op!("typeof"), ..
+            }) => self.has_typeof = true,
+            Expr::Ident(Ident {
+                sym: js_word!("Symbol"),
+                ..
+            }) => self.uses_symbol = true,
+            Expr::Bin(BinExpr { op: op!("**"), .. })
+            | Expr::Assign(AssignExpr { op: op!("**="), .. }) => {
+                self.add(Feature::ExponentiationOperator)
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Visit<Lit> for FeatureDetector {
+    fn visit(&mut self, lit: &Lit) {
+        lit.visit_children(self);
+
+        match *lit {
+            Lit::Regex(Regex {
+                ref exp, ref flags, ..
+            }) => self.check_regex(exp, flags),
+            Lit::Str(Str { ref value, .. }) => {
+                if value.contains('\u{2028}') || value.contains('\u{2029}') {
+                    self.add(Feature::JsonStrings)
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Visit<Function> for FeatureDetector {
+    fn visit(&mut self, f: &Function) {
+        f.visit_children(self);
+
+        self.check_params(&f.params);
+
+        match (f.is_async, f.is_generator) {
+            (true, true) => self.add(Feature::AsyncGeneratorFunctions),
+            (true, false) => self.add(Feature::AsyncToGenerator),
+            (false, true) => self.add(Feature::Regenerator),
+            (false, false) => {}
+        }
+    }
+}
+
+impl Visit<ArrowExpr> for FeatureDetector {
+    fn visit(&mut self, f: &ArrowExpr) {
+        f.visit_children(self);
+
+        self.check_params(&f.params);
+
+        if f.is_async {
+            self.add(Feature::AsyncToGenerator);
+        }
+    }
+}
+
+impl Visit<Class> for FeatureDetector {
+    fn visit(&mut self, c: &Class) {
+        let old = self.in_object_lit;
+        self.in_object_lit = false;
+        c.visit_children(self);
+        self.in_object_lit = old;
+
+        self.add(Feature::Classes);
+    }
+}
+
+impl Visit<ClassMember> for FeatureDetector {
+    fn visit(&mut self, m: &ClassMember) {
+        m.visit_children(self);
+
+        match *m {
+            ClassMember::ClassProp(..) => self.found.class_properties = true,
+            ClassMember::PrivateProp(..) => self.found.private_properties = true,
+            _ => {}
+        }
+    }
+}
+
+impl Visit<ExprOrSuper> for FeatureDetector {
+    fn visit(&mut self, e: &ExprOrSuper) {
+        e.visit_children(self);
+
+        // `super` in a class is handled by the class transform.
+        if let ExprOrSuper::Super(..) = *e {
+            if self.in_object_lit {
+                self.add(Feature::ObjectSuper);
+            }
+        }
+    }
+}
+
+impl Visit<ObjectLit> for FeatureDetector {
+    fn visit(&mut self, obj: &ObjectLit) {
+        let old = self.in_object_lit;
+        self.in_object_lit = true;
+        obj.visit_children(self);
+        self.in_object_lit = old;
+
+        let mut keys: FxHashSet<JsWord> = Default::default();
+        let mut add_key = |v: &mut Self, key: &PropName| {
+            let sym = match *key {
+                PropName::Ident(ref i) => i.sym.clone(),
+                PropName::Str(ref s) => s.value.clone(),
+                PropName::Num(..) => return,
+                PropName::Computed(..) => {
+                    v.add(Feature::ComputedProperties);
+                    return;
+                }
+            };
+
+            if !keys.insert(sym) {
+                v.add(Feature::DuplicateKeys);
+            }
+        };
+
+        for p in &obj.props {
+            match *p {
+                PropOrSpread::Spread(..) => self.add(Feature::ObjectRestSpread),
+                PropOrSpread::Prop(ref p) => match **p {
+                    Prop::Shorthand(ref i) => {
+                        self.add(Feature::ShorthandProperties);
+                        add_key(self, &PropName::Ident(i.clone()));
+                    }
+                    Prop::Method(MethodProp { ref key, .. }) => {
+                        self.add(Feature::ShorthandProperties);
+                        add_key(self, key);
+                    }
+                    Prop::KeyValue(KeyValueProp { ref key, .. }) => add_key(self, key),
+                    // Getter / setter pairs are not duplicates.
+                    Prop::Getter(GetterProp {
+                        key: PropName::Computed(..),
+                        ..
+                    })
+                    | Prop::Setter(SetterProp {
+                        key: PropName::Computed(..),
+                        ..
+                    }) => self.add(Feature::ComputedProperties),
+                    _ => {}
+                },
+            }
+        }
+    }
+}
+
+impl Visit<ExprOrSpread> for FeatureDetector {
+    fn visit(&mut self, e: &ExprOrSpread) {
+        e.visit_children(self);
+
+        if e.spread.is_some() {
+            self.add(Feature::Spread);
+        }
+    }
+}
+
+impl Visit<Pat> for FeatureDetector {
+    fn visit(&mut self, p: &Pat) {
+        p.visit_children(self);
+
+        match *p {
+            Pat::Array(..) | Pat::Object(..) => self.add(Feature::Destructuring),
+            _ => {}
+        }
+    }
+}
+
+impl Visit<ObjectPatProp> for FeatureDetector {
+    fn visit(&mut self, p: &ObjectPatProp) {
+        p.visit_children(self);
+
+        if let ObjectPatProp::Rest(..) = *p {
+            self.add(Feature::ObjectRestSpread);
+        }
+    }
+}
+
+impl Visit<Stmt> for FeatureDetector {
+    fn visit(&mut self, s: &Stmt) {
+        s.visit_children(self);
+
+        match *s {
+            // Function bodies are not `Stmt::Block`, so this only matches
+            // functions declared in a nested block.
+            Stmt::Block(BlockStmt { ref stmts, .. }) => {
+                let has_fn = stmts.iter().any(|s| match *s {
+                    Stmt::Decl(Decl::Fn(..)) => true,
+                    _ => false,
+                });
+                if has_fn {
+                    self.add(Feature::BlockScopedFunctions);
+                }
+            }
+            Stmt::ForOf(ForOfStmt { await_token, .. }) => {
+                self.add(Feature::ForOf);
+
+                if await_token.is_some() {
+                    self.add(Feature::AsyncGeneratorFunctions);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Visit<VarDecl> for FeatureDetector {
+    fn visit(&mut self, v: &VarDecl) {
+        v.visit_children(self);
+
+        match v.kind {
+            VarDeclKind::Let | VarDeclKind::Const => self.add(Feature::BlockScoping),
+            VarDeclKind::Var => {}
+        }
+    }
+}
+
+impl Visit<CatchClause> for FeatureDetector {
+    fn visit(&mut self, c: &CatchClause) {
+        c.visit_children(self);
+
+        if c.param.is_none() {
+            self.add(Feature::OptionalCatchBinding);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use swc_ecma_parser::{EsConfig, Parser, Session, SourceFileInput, Syntax};
+    use testing::Tester;
+
+    fn detect(src: &str) -> FeatureSet {
+        Tester::new()
+            .print_errors(|cm, handler| {
+                let fm = cm.new_source_file(swc_common::FileName::Anon, src.into());
+                let mut p = Parser::new(
+                    Session { handler: &handler },
+                    Syntax::Es(EsConfig {
+                        class_private_props: true,
+                        class_props: true,
+                        ..Default::default()
+                    }),
+                    SourceFileInput::from(&*fm),
+                    None,
+                );
+
+                let module = p.parse_module().map_err(|mut e| e.emit())?;
+
+                Ok(detect_features(&Program::Module(module)))
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn es5() {
+        let f = detect("var a = function (b) { return typeof b + 1 }; a(1);");
+
+        assert!(f.features.is_empty());
+        assert!(!f.optional_chaining);
+    }
+
+    #[test]
+    fn arrow_and_template() {
+        let f = detect("const a = (b) => `${b}`;");
+
+        assert!(f.contains(Feature::ArrowFunctions));
+        assert!(f.contains(Feature::TemplateLiterals));
+        assert!(f.contains(Feature::BlockScoping));
+        assert!(!f.contains(Feature::Parameters));
+    }
+
+    #[test]
+    fn parameters_and_destructuring() {
+        let f = detect("function a({ b }, c = 1, ...d) {}");
+
+        assert!(f.contains(Feature::Parameters));
+        assert!(f.contains(Feature::Destructuring));
+        assert!(!f.contains(Feature::Spread));
+    }
+
+    #[test]
+    fn async_generator() {
+        let f = detect("async function* a() { for await (const b of c) {} }");
+
+        assert!(f.contains(Feature::AsyncGeneratorFunctions));
+        assert!(f.contains(Feature::ForOf));
+        assert!(!f.contains(Feature::Regenerator));
+    }
+
+    #[test]
+    fn object_literal() {
+        let f = detect("x = { a, b() {}, [c]: 1, a: 2, ...d };");
+
+        assert!(f.contains(Feature::ShorthandProperties));
+        assert!(f.contains(Feature::ComputedProperties));
+        assert!(f.contains(Feature::DuplicateKeys));
+        assert!(f.contains(Feature::ObjectRestSpread));
+    }
+
+    #[test]
+    fn regex() {
+        let f = detect("/(?<year>\\d+)(?<=a)/su; /a/y;");
+
+        assert!(f.contains(Feature::NamedCapturingGroupsRegex));
+        assert!(f.contains(Feature::DotAllRegex));
+        assert!(f.contains(Feature::UnicodeRegex));
+        assert!(f.contains(Feature::StickyRegex));
+        assert!(!f.contains(Feature::UnicodePropertyRegex));
+    }
+
+    #[test]
+    fn proposals() {
+        let f = detect("class A { a = 1; #b = 2; c() { return this?.a } }");
+
+        assert!(f.contains(Feature::Classes));
+        assert!(f.class_properties);
+        assert!(f.private_properties);
+        assert!(f.optional_chaining);
+    }
+
+    #[test]
+    fn misc() {
+        let f = detect("try {} catch {} a **= 2; { function b() {} }");
+
+        assert!(f.contains(Feature::OptionalCatchBinding));
+        assert!(f.contains(Feature::ExponentiationOperator));
+        assert!(f.contains(Feature::BlockScopedFunctions));
+    }
+
+    #[test]
+    fn object_super() {
+        let f = detect("class A extends B { m() { super.m() } }");
+        assert!(!f.contains(Feature::ObjectSuper));
+
+        let f = detect("x = { m() { return super.m() } };");
+        assert!(f.contains(Feature::ObjectSuper));
+
+        let f = detect("x = { a: class extends B { m() { super.m() } } };");
+        assert!(!f.contains(Feature::ObjectSuper));
+    }
+
+    #[test]
+    fn typeof_symbol() {
+        let f = detect("typeof a; a = Symbol();");
+        assert!(f.contains(Feature::TypeOfSymbol));
+
+        let f = detect("a = Symbol();");
+        assert!(!f.contains(Feature::TypeOfSymbol));
+    }
+}
@@ -4,7 +4,11 @@
 #![feature(trace_macros)]
 #![recursion_limit = "256"]
 
-pub use self::{transform_data::Feature, version::Version};
+pub use self::{
+    detect::{detect_features, FeatureSet},
+    transform_data::Feature,
+    version::Version,
+};
 use chashmap::CHashMap;
 use fxhash::{FxHashMap, FxHashSet};
 use once_cell::sync::Lazy;
@@ -27,6 +31,7 @@ use swc_ecma_transforms::{
 mod util;
 mod corejs2;
 mod corejs3;
+mod detect;
 mod regenerator;
 mod transform_data;
 mod version;
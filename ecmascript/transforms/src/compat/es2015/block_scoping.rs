@@ -3,21 +3,12 @@ use ast::*;
 use smallvec::SmallVec;
 use std::mem::replace;
 use swc_common::{util::map::Map, Fold, FoldWith, Spanned, Visit, VisitWith, DUMMY_SP};
-use utils::{find_ids, ident::IdentLike, prepend, var::VarCollector, ExprFactory, Id, StmtLike};
+use utils::{find_ids, prepend, var::VarCollector, ExprFactory, Id, StmtLike};
 
+/// Converts `let` and `const` to `var`.
 ///
-///
-/// TODO(kdy1): Optimization
-///
-/// ```js
-/// let functions = [];
-/// for (let i = 0; i < 10; i++) {
-/// 	functions.push(function() {
-///        let i = 1;
-/// 		console.log(i);
-/// 	});
-/// }
-/// ```
+/// The body of a loop is moved into a function only if a closure created in
+/// it captures a variable declared by the loop.
 pub fn block_scoping() -> impl Pass {
     BlockScoping::default()
 }
@@ -27,12 +18,7 @@ type ScopeStack = SmallVec<[ScopeKind; 8]>;
 #[derive(Debug, PartialEq, Eq)]
 enum ScopeKind {
     Loop,
-    ForLetLoop {
-        all: Vec<Id>,
-        args: Vec<Id>,
-        /// Produced by identifier reference and consumed by for-of/in loop.
-        used: Vec<Id>,
-    },
+    ForLetLoop { args: Vec<Id> },
     Fn,
     Block,
 }
@@ -64,23 +50,15 @@ impl BlockScoping {
         node
     }
 
-    fn mark_as_used(&mut self, i: Id) {
-        for (idx, scope) in self.scope.iter_mut().rev().enumerate() {
-            match scope {
-                ScopeKind::ForLetLoop { all, used, .. } => {
-                    //
-                    if all.contains(&i) {
-                        if idx == 0 {
-                            return;
-                        }
-
-                        used.push(i);
-                        return;
-                    }
-                }
-                _ => {}
-            }
+    /// `vars` is the list of variables declared by the loop, including
+    /// infected ones.
+    fn fold_loop_body(&mut self, args: Vec<Id>, vars: &[Id], body: Box<Stmt>) -> Box<Stmt> {
+        if !is_captured(vars, &body) {
+            return self.fold_with_scope(ScopeKind::Loop, body);
         }
+
+        let body = self.fold_with_scope(ScopeKind::ForLetLoop { args }, body);
+        self.handle_vars(body)
     }
 
     fn in_loop_body(&self) -> bool {
@@ -96,11 +74,7 @@ impl BlockScoping {
     fn handle_vars(&mut self, body: Box<Stmt>) -> Box<Stmt> {
         body.map(|body| {
             //
-            if let Some(ScopeKind::ForLetLoop { args, used, .. }) = self.scope.pop() {
-                if used.is_empty() {
-                    return body;
-                }
-
+            if let Some(ScopeKind::ForLetLoop { args }) = self.scope.pop() {
                 let var_name = private_ident!("_loop");
 
                 self.vars.push(VarDeclarator {
@@ -187,17 +161,7 @@ impl Fold<ForStmt> for BlockScoping {
 
         find_infected(&mut vars, &node.body);
 
-        let kind = if vars.is_empty() {
-            ScopeKind::Loop
-        } else {
-            ScopeKind::ForLetLoop {
-                all: vars,
-                args,
-                used: vec![],
-            }
-        };
-        let body = self.fold_with_scope(kind, node.body);
-        let body = self.handle_vars(body);
+        let body = self.fold_loop_body(args, &vars, node.body);
 
         ForStmt {
             init,
@@ -219,17 +183,7 @@ impl Fold<ForOfStmt> for BlockScoping {
 
         find_infected(&mut vars, &node.body);
 
-        let kind = if vars.is_empty() {
-            ScopeKind::Loop
-        } else {
-            ScopeKind::ForLetLoop {
-                all: vars,
-                args,
-                used: vec![],
-            }
-        };
-        let body = self.fold_with_scope(kind, node.body);
-        let body = self.handle_vars(body);
+        let body = self.fold_loop_body(args, &vars, node.body);
 
         ForOfStmt {
             left,
@@ -250,17 +204,7 @@ impl Fold<ForInStmt> for BlockScoping {
 
         find_infected(&mut vars, &node.body);
 
-        let kind = if vars.is_empty() {
-            ScopeKind::Loop
-        } else {
-            ScopeKind::ForLetLoop {
-                all: vars,
-                args,
-                used: vec![],
-            }
-        };
-        let body = self.fold_with_scope(kind, node.body);
-        let body = self.handle_vars(body);
+        let body = self.fold_loop_body(args, &vars, node.body);
 
         ForInStmt {
             left,
@@ -349,15 +293,6 @@ impl Fold<VarDeclarator> for BlockScoping {
     }
 }
 
-impl Fold<Ident> for BlockScoping {
-    fn fold(&mut self, node: Ident) -> Ident {
-        let id = node.to_id();
-        self.mark_as_used(id);
-
-        node
-    }
-}
-
 impl<T> Fold<Vec<T>> for BlockScoping
 where
     T: StmtLike,
@@ -476,6 +411,82 @@ impl Visit<Ident> for InfectionFinder<'_> {
     }
 }
 
+/// Returns true if a closure created in `body` captures one of `vars`.
+fn is_captured<T>(vars: &[Id], body: &T) -> bool
+where
+    T: for<'any> VisitWith<CaptureFinder<'any>>,
+{
+    let mut v = CaptureFinder {
+        vars,
+        in_closure: false,
+        found: false,
+    };
+    body.visit_with(&mut v);
+
+    v.found
+}
+
+struct CaptureFinder<'a> {
+    vars: &'a [Id],
+    in_closure: bool,
+    found: bool,
+}
+
+impl CaptureFinder<'_> {
+    fn visit_closure<T>(&mut self, node: &T)
+    where
+        T: VisitWith<Self>,
+    {
+        if self.found {
+            return;
+        }
+
+        let old = replace(&mut self.in_closure, true);
+        node.visit_children(self);
+        self.in_closure = old;
+    }
+}
+
+macro_rules! closure {
+    ($T:ty) => {
+        impl Visit<$T> for CaptureFinder<'_> {
+            fn visit(&mut self, node: &$T) {
+                self.visit_closure(node)
+            }
+        }
+    };
+}
+
+closure!(Function);
+closure!(ArrowExpr);
+closure!(GetterProp);
+closure!(SetterProp);
+// Class properties are evaluated on construction.
+closure!(Class);
+
+impl Visit<MemberExpr> for CaptureFinder<'_> {
+    fn visit(&mut self, e: &MemberExpr) {
+        e.obj.visit_with(self);
+
+        if e.computed {
+            e.prop.visit_with(self);
+        }
+    }
+}
+
+impl Visit<Ident> for CaptureFinder<'_> {
+    fn visit(&mut self, i: &Ident) {
+        if !self.in_closure || self.found {
+            return;
+        }
+
+        self.found = self
+            .vars
+            .iter()
+            .any(|id| i.span.ctxt() == id.1 && i.sym == id.0);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::block_scoping;
@@ -558,6 +569,45 @@ expect(functions[7]()).toBe(7);
 "
     );
 
+    test!(
+        ::swc_ecma_parser::Syntax::default(),
+        |_| block_scoping(),
+        for_let_loop_not_captured,
+        "let x = 1;
+for (let i = 0; i < 10; i++) {
+    for (let j = 0; j < 10; j++) {
+        console.log(i, j);
+    }
+    foo(function() {
+        return x;
+    });
+}",
+        "var x = 1;
+for (var i = 0; i < 10; i++) {
+    for (var j = 0; j < 10; j++) {
+        console.log(i, j);
+    }
+    foo(function() {
+        return x;
+    });
+}"
+    );
+
+    test_exec!(
+        ::swc_ecma_parser::Syntax::default(),
+        |_| block_scoping(),
+        for_let_loop_nested_exec,
+        "let functions = [];
+for (let i = 0; i < 2; i++) {
+    for (let j = 0; j < 2; j++) {
+        functions.push(() => i);
+    }
+}
+expect(functions[1]()).toBe(0);
+expect(functions[2]()).toBe(1);
+"
+    );
+
     test_exec!(
         ::swc_ecma_parser::Syntax::default(),
         |_| block_scoping(),
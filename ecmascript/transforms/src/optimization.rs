@@ -1,9 +1,13 @@
 pub use self::{
+    compress_literals::CompressLiterals,
     inline_globals::InlineGlobals,
     json_parse::JsonParse,
-    simplify::{expr_simplifier, simplifier},
+    normalize_undefined::NormalizeUndefined,
+    simplify::{dce, dce_until_fixed_point, expr_simplifier, minimize_conditions, simplifier},
 };
 
+mod compress_literals;
 mod inline_globals;
 mod json_parse;
+mod normalize_undefined;
 mod simplify;
@@ -0,0 +1,265 @@
+use crate::util::declares;
+use ast::*;
+use swc_atoms::js_word;
+use swc_common::{Fold, FoldWith};
+
+/// Shortens literals: `true` to `!0`, `false` to `!1` and `Infinity` to
+/// `1/0`.
+///
+/// Operands which bind tighter than `!` (e.g. callees and objects of member
+/// expressions) are left as is. Other operands may need parentheses (e.g.
+/// `typeof (1 / 0)`), so `fixer` should be run after this pass.
+///
+/// A scope declaring `Infinity` is left as is.
+#[derive(Debug, Default)]
+pub struct CompressLiterals;
+
+impl Fold<Module> for CompressLiterals {
+    fn fold(&mut self, m: Module) -> Module {
+        let shadowed = declares(&m.body, &js_word!("Infinity"));
+        m.fold_children(&mut Compressor { shadowed })
+    }
+}
+
+impl Fold<Script> for CompressLiterals {
+    fn fold(&mut self, s: Script) -> Script {
+        let shadowed = declares(&s.body, &js_word!("Infinity"));
+        s.fold_children(&mut Compressor { shadowed })
+    }
+}
+
+struct Compressor {
+    /// True if `Infinity` is declared in the current scope or one of its
+    /// parents.
+    shadowed: bool,
+}
+
+impl Compressor {
+    fn fold_scope<T>(&mut self, node: T, shadowed: bool) -> T
+    where
+        T: FoldWith<Self>,
+    {
+        let old = self.shadowed;
+        self.shadowed |= shadowed;
+        let node = node.fold_children(self);
+        self.shadowed = old;
+        node
+    }
+
+    /// Folds children of `e`, but not `e` itself.
+    fn fold_operand(&mut self, e: Box<Expr>) -> Box<Expr> {
+        match *e {
+            Expr::Lit(Lit::Bool(..)) | Expr::Ident(..) => e,
+            _ => e.fold_with(self),
+        }
+    }
+
+    fn fold_callee(&mut self, callee: ExprOrSuper) -> ExprOrSuper {
+        match callee {
+            ExprOrSuper::Expr(e) => ExprOrSuper::Expr(self.fold_operand(e)),
+            _ => callee,
+        }
+    }
+}
+
+impl Fold<Function> for Compressor {
+    fn fold(&mut self, f: Function) -> Function {
+        let shadowed =
+            declares(&f.params, &js_word!("Infinity")) || declares(&f.body, &js_word!("Infinity"));
+        self.fold_scope(f, shadowed)
+    }
+}
+
+impl Fold<ArrowExpr> for Compressor {
+    fn fold(&mut self, f: ArrowExpr) -> ArrowExpr {
+        let shadowed =
+            declares(&f.params, &js_word!("Infinity")) || declares(&f.body, &js_word!("Infinity"));
+        self.fold_scope(f, shadowed)
+    }
+}
+
+impl Fold<FnExpr> for Compressor {
+    fn fold(&mut self, f: FnExpr) -> FnExpr {
+        let shadowed = is_infinity(&f.ident);
+        self.fold_scope(f, shadowed)
+    }
+}
+
+impl Fold<ClassExpr> for Compressor {
+    fn fold(&mut self, c: ClassExpr) -> ClassExpr {
+        let shadowed = is_infinity(&c.ident);
+        self.fold_scope(c, shadowed)
+    }
+}
+
+impl Fold<MemberExpr> for Compressor {
+    fn fold(&mut self, e: MemberExpr) -> MemberExpr {
+        MemberExpr {
+            obj: self.fold_callee(e.obj),
+            prop: if e.computed {
+                e.prop.fold_with(self)
+            } else {
+                e.prop
+            },
+            ..e
+        }
+    }
+}
+
+impl Fold<CallExpr> for Compressor {
+    fn fold(&mut self, e: CallExpr) -> CallExpr {
+        CallExpr {
+            callee: self.fold_callee(e.callee),
+            args: e.args.fold_with(self),
+            ..e
+        }
+    }
+}
+
+impl Fold<NewExpr> for Compressor {
+    fn fold(&mut self, e: NewExpr) -> NewExpr {
+        NewExpr {
+            callee: self.fold_operand(e.callee),
+            args: e.args.fold_with(self),
+            ..e
+        }
+    }
+}
+
+impl Fold<TaggedTpl> for Compressor {
+    fn fold(&mut self, e: TaggedTpl) -> TaggedTpl {
+        TaggedTpl {
+            tag: self.fold_operand(e.tag),
+            exprs: e.exprs.fold_with(self),
+            ..e
+        }
+    }
+}
+
+impl Fold<BinExpr> for Compressor {
+    fn fold(&mut self, e: BinExpr) -> BinExpr {
+        match e.op {
+            op!("**") => BinExpr {
+                left: self.fold_operand(e.left),
+                right: e.right.fold_with(self),
+                ..e
+            },
+            _ => e.fold_children(self),
+        }
+    }
+}
+
+/// Assignment targets are references.
+impl Fold<PatOrExpr> for Compressor {
+    fn fold(&mut self, p: PatOrExpr) -> PatOrExpr {
+        match p {
+            PatOrExpr::Expr(e) => PatOrExpr::Expr(self.fold_operand(e)),
+            PatOrExpr::Pat(..) => p.fold_children(self),
+        }
+    }
+}
+
+/// Update targets are references.
+impl Fold<UpdateExpr> for Compressor {
+    fn fold(&mut self, e: UpdateExpr) -> UpdateExpr {
+        UpdateExpr {
+            arg: self.fold_operand(e.arg),
+            ..e
+        }
+    }
+}
+
+impl Fold<Expr> for Compressor {
+    fn fold(&mut self, e: Expr) -> Expr {
+        let e = e.fold_children(self);
+
+        match e {
+            Expr::Lit(Lit::Bool(Bool { span, value })) => Expr::Unary(UnaryExpr {
+                span,
+                op: op!("!"),
+                arg: box Expr::Lit(Lit::Num(Number {
+                    span,
+                    value: if value { 0.0 } else { 1.0 },
+                })),
+            }),
+
+            Expr::Ident(Ident {
+                sym: js_word!("Infinity"),
+                span,
+                ..
+            }) if !self.shadowed => Expr::Bin(BinExpr {
+                span,
+                op: op!("/"),
+                left: box Expr::Lit(Lit::Num(Number { span, value: 1.0 })),
+                right: box Expr::Lit(Lit::Num(Number { span, value: 0.0 })),
+            }),
+
+            _ => e,
+        }
+    }
+}
+
+fn is_infinity(i: &Option<Ident>) -> bool {
+    match *i {
+        Some(Ident {
+            sym: js_word!("Infinity"),
+            ..
+        }) => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    test!(
+        ::swc_ecma_parser::Syntax::default(),
+        |_| CompressLiterals,
+        simple,
+        "x = true; y = false; z = Infinity; f(true, -Infinity);",
+        "x = !0; y = !1; z = 1 / 0; f(!0, -(1 / 0));"
+    );
+
+    test!(
+        ::swc_ecma_parser::Syntax::default(),
+        |_| CompressLiterals,
+        operators,
+        "a = true + 1; b = typeof Infinity; c = x ? true : Infinity; d = 2 ** Infinity;",
+        "a = !0 + 1; b = typeof (1 / 0); c = x ? !0 : 1 / 0; d = 2 ** (1 / 0);"
+    );
+
+    test!(
+        ::swc_ecma_parser::Syntax::default(),
+        |_| CompressLiterals,
+        tight_operands,
+        "true.toString(); Infinity(); new Infinity(); true``; true ** 2; a.true;
+        Infinity += 1; Infinity++; a[true];",
+        "true.toString(); Infinity(); new Infinity(); true``; true ** 2; a.true;
+        Infinity += 1; Infinity++; a[!0];"
+    );
+
+    test!(
+        ::swc_ecma_parser::Syntax::default(),
+        |_| CompressLiterals,
+        shadowed_param,
+        "function f(Infinity) { return Infinity; } function g() { return Infinity; }",
+        "function f(Infinity) { return Infinity; } function g() { return 1 / 0; }"
+    );
+
+    test!(
+        ::swc_ecma_parser::Syntax::default(),
+        |_| CompressLiterals,
+        shadowed_let,
+        "function f() { { let Infinity = 1; g(Infinity, true); } } h(Infinity);",
+        "function f() { { let Infinity = 1; g(Infinity, !0); } } h(1 / 0);"
+    );
+
+    test!(
+        ::swc_ecma_parser::Syntax::default(),
+        |_| CompressLiterals,
+        shadowed_top_level,
+        "var Infinity = 1; function f() { return Infinity; }",
+        "var Infinity = 1; function f() { return Infinity; }"
+    );
+}
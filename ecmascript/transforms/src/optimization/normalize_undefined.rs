@@ -0,0 +1,207 @@
+use crate::util::declares;
+use ast::*;
+use swc_atoms::js_word;
+use swc_common::{Fold, FoldWith};
+
+/// Rewrites references to the global `undefined` to `void 0`, which is
+/// shorter and cannot be shadowed.
+///
+/// If [NormalizeUndefined::readable] is true, `void 0` is rewritten to
+/// `undefined` instead.
+///
+/// A scope declaring `undefined` (e.g. `function f(undefined) {}`) is left as
+/// is.
+#[derive(Debug, Default)]
+pub struct NormalizeUndefined {
+    pub readable: bool,
+}
+
+impl Fold<Module> for NormalizeUndefined {
+    fn fold(&mut self, m: Module) -> Module {
+        let shadowed = declares(&m.body, &js_word!("undefined"));
+        m.fold_children(&mut Normalizer {
+            readable: self.readable,
+            shadowed,
+        })
+    }
+}
+
+impl Fold<Script> for NormalizeUndefined {
+    fn fold(&mut self, s: Script) -> Script {
+        let shadowed = declares(&s.body, &js_word!("undefined"));
+        s.fold_children(&mut Normalizer {
+            readable: self.readable,
+            shadowed,
+        })
+    }
+}
+
+struct Normalizer {
+    readable: bool,
+    /// True if `undefined` is declared in the current scope or one of its
+    /// parents.
+    shadowed: bool,
+}
+
+impl Normalizer {
+    fn fold_scope<T>(&mut self, node: T, shadowed: bool) -> T
+    where
+        T: FoldWith<Self>,
+    {
+        let old = self.shadowed;
+        self.shadowed |= shadowed;
+        let node = node.fold_children(self);
+        self.shadowed = old;
+        node
+    }
+}
+
+impl Fold<Function> for Normalizer {
+    fn fold(&mut self, f: Function) -> Function {
+        let shadowed = declares(&f.params, &js_word!("undefined"))
+            || declares(&f.body, &js_word!("undefined"));
+        self.fold_scope(f, shadowed)
+    }
+}
+
+impl Fold<ArrowExpr> for Normalizer {
+    fn fold(&mut self, f: ArrowExpr) -> ArrowExpr {
+        let shadowed = declares(&f.params, &js_word!("undefined"))
+            || declares(&f.body, &js_word!("undefined"));
+        self.fold_scope(f, shadowed)
+    }
+}
+
+impl Fold<FnExpr> for Normalizer {
+    fn fold(&mut self, f: FnExpr) -> FnExpr {
+        let shadowed = is_undefined(&f.ident);
+        self.fold_scope(f, shadowed)
+    }
+}
+
+impl Fold<ClassExpr> for Normalizer {
+    fn fold(&mut self, c: ClassExpr) -> ClassExpr {
+        let shadowed = is_undefined(&c.ident);
+        self.fold_scope(c, shadowed)
+    }
+}
+
+impl Fold<MemberExpr> for Normalizer {
+    fn fold(&mut self, e: MemberExpr) -> MemberExpr {
+        if e.computed {
+            e.fold_children(self)
+        } else {
+            MemberExpr {
+                obj: e.obj.fold_with(self),
+                ..e
+            }
+        }
+    }
+}
+
+impl Fold<Expr> for Normalizer {
+    fn fold(&mut self, e: Expr) -> Expr {
+        let e = e.fold_children(self);
+
+        if self.shadowed {
+            return e;
+        }
+
+        match e {
+            Expr::Ident(Ident {
+                sym: js_word!("undefined"),
+                span,
+                ..
+            }) if !self.readable => Expr::Unary(UnaryExpr {
+                span,
+                op: op!("void"),
+                arg: box Expr::Lit(Lit::Num(Number { span, value: 0.0 })),
+            }),
+
+            Expr::Unary(UnaryExpr {
+                span,
+                op: op!("void"),
+                arg: box Expr::Lit(Lit::Num(Number { value, .. })),
+            }) if self.readable && value == 0.0 => {
+                Expr::Ident(Ident::new(js_word!("undefined"), span))
+            }
+
+            _ => e,
+        }
+    }
+}
+
+fn is_undefined(i: &Option<Ident>) -> bool {
+    match *i {
+        Some(Ident {
+            sym: js_word!("undefined"),
+            ..
+        }) => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    test!(
+        ::swc_ecma_parser::Syntax::default(),
+        |_| NormalizeUndefined::default(),
+        simple,
+        "x = undefined; foo(undefined); typeof undefined;",
+        "x = void 0; foo(void 0); typeof void 0;"
+    );
+
+    test!(
+        ::swc_ecma_parser::Syntax::default(),
+        |_| NormalizeUndefined::default(),
+        member,
+        "x = a.undefined; y = a[undefined]; z = {undefined: 1, undefined}",
+        "x = a.undefined; y = a[void 0]; z = {undefined: 1, undefined}"
+    );
+
+    test!(
+        ::swc_ecma_parser::Syntax::default(),
+        |_| NormalizeUndefined::default(),
+        shadowed_param,
+        "function f(undefined) { return undefined; }
+        function g() { return undefined; }",
+        "function f(undefined) { return undefined; }
+        function g() { return void 0; }"
+    );
+
+    test!(
+        ::swc_ecma_parser::Syntax::default(),
+        |_| NormalizeUndefined::default(),
+        shadowed_var,
+        "function f() { if (a) { var undefined = 1; } return () => undefined; }
+        x = undefined;",
+        "function f() { if (a) { var undefined = 1; } return () => undefined; }
+        x = void 0;"
+    );
+
+    test!(
+        ::swc_ecma_parser::Syntax::default(),
+        |_| NormalizeUndefined::default(),
+        shadowed_top_level,
+        "let undefined = 1; function f() { return undefined; }",
+        "let undefined = 1; function f() { return undefined; }"
+    );
+
+    test!(
+        ::swc_ecma_parser::Syntax::default(),
+        |_| NormalizeUndefined::default(),
+        shadowed_catch,
+        "try {} catch (undefined) { x = undefined; }",
+        "try {} catch (undefined) { x = undefined; }"
+    );
+
+    test!(
+        ::swc_ecma_parser::Syntax::default(),
+        |_| NormalizeUndefined { readable: true },
+        readable,
+        "x = void 0; function f(undefined) { return void 0; } y = void foo();",
+        "x = undefined; function f(undefined) { return void 0; } y = void foo();"
+    );
+}
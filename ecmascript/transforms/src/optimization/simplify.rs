@@ -1,12 +1,16 @@
 //! Ported from closure compiler.
-pub use self::dce::dce;
 use self::expr::SimplifyExpr;
+pub use self::{
+    dce::{dce, dce_until_fixed_point},
+    minimize_conditions::minimize_conditions,
+};
 use crate::pass::Pass;
 use ast::*;
 use swc_common::{Fold, FoldWith};
 
 pub mod dce;
 mod expr;
+mod minimize_conditions;
 
 /// Not intended for general use. Use [simplifier] instead.
 ///
@@ -25,6 +29,7 @@ struct Simplifier;
 
 impl Fold<Program> for Simplifier {
     fn fold(&mut self, p: Program) -> Program {
-        p.fold_with(&mut expr_simplifier()).fold_with(&mut dce())
+        p.fold_with(&mut expr_simplifier())
+            .fold_with(&mut dce(Default::default()))
     }
 }
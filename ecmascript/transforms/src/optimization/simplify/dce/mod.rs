@@ -3,8 +3,9 @@ use crate::{
     util::{StmtLike, *},
 };
 use ast::*;
-use std::{cmp::min, iter::once};
-use swc_atoms::js_word;
+use fxhash::FxHashSet;
+use std::{cmp::min, iter::once, mem::replace};
+use swc_atoms::{js_word, JsWord};
 use swc_common::{
     fold::VisitWith, util::move_map::MoveMap, Fold, FoldWith, Spanned, Visit, DUMMY_SP,
 };
@@ -13,13 +14,180 @@ use swc_common::{
 mod tests;
 
 /// Ported from `PeepholeRemoveDeadCode` of google closure compiler.
-pub fn dce() -> impl Pass + 'static {
-    Remover::default()
+pub fn dce(config: Config) -> impl Pass + 'static {
+    Remover {
+        config,
+        ..Default::default()
+    }
+}
+
+/// Runs [dce] until the program stops changing.
+///
+/// Removing code may make other code dead, e.g. a variable which was only
+/// referenced by a removed declaration.
+pub fn dce_until_fixed_point(config: Config) -> impl Pass + 'static {
+    UntilFixedPoint { config }
+}
+
+/// Safety cap for [dce_until_fixed_point].
+const MAX_ITERATIONS: usize = 100;
+
+struct UntilFixedPoint {
+    config: Config,
+}
+
+impl UntilFixedPoint {
+    fn fold_until_fixed_point<T>(&self, mut node: T) -> T
+    where
+        T: FoldWith<Remover> + Clone + PartialEq,
+    {
+        for _ in 0..MAX_ITERATIONS {
+            let mut remover = Remover {
+                config: self.config,
+                ..Default::default()
+            };
+            let folded = node.clone().fold_with(&mut remover);
+
+            if folded == node {
+                break;
+            }
+            node = folded;
+        }
+
+        node
+    }
+}
+
+impl Fold<Module> for UntilFixedPoint {
+    fn fold(&mut self, m: Module) -> Module {
+        self.fold_until_fixed_point(m)
+    }
+}
+
+impl Fold<Script> for UntilFixedPoint {
+    fn fold(&mut self, s: Script) -> Script {
+        self.fold_until_fixed_point(s)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Config {
+    /// Removes `var`, `let` and `const` declarations which are never
+    /// referenced.
+    ///
+    /// Initializers with side effects are preserved as expression statements.
+    /// Top-level variables of a script are kept, as other scripts can access
+    /// them.
+    pub remove_unused_vars: bool,
+
+    /// Removes labels which are not referenced by `break` or `continue`.
+    pub remove_unused_labels: bool,
+
+    /// Removes import specifiers which are never referenced.
+    ///
+    /// An import declaration without any remaining specifier is removed too,
+    /// unless [Config::keep_import_side_effects] is enabled.
+    pub remove_unused_imports: bool,
+
+    /// Keeps `import 'foo'` for import declarations whose specifiers are all
+    /// removed, as importing a module may have side effects.
+    pub keep_import_side_effects: bool,
 }
 
 #[derive(Debug, Default)]
 struct Remover {
+    config: Config,
+
     normal_block: bool,
+
+    /// Referenced identifiers.
+    ///
+    /// This is [None] if [Config::remove_unused_vars] is disabled.
+    usage: Option<FxHashSet<Id>>,
+
+    /// True if the current function or a function nested in it contains a
+    /// direct `eval` or a `with` statement, which can reference any variable
+    /// of the scope.
+    in_poisoned_scope: bool,
+
+    /// True while folding the global scope of a script.
+    in_global_scope: bool,
+
+    /// Label of the statement being folded.
+    label: Option<JsWord>,
+
+    /// Functions annotated with `/*#__SIDE_EFFECT_FREE__*/`.
+    ///
+    /// Calls to these are treated as pure.
+    pure_fns: FxHashSet<Id>,
+}
+
+impl Fold<Module> for Remover {
+    fn fold(&mut self, m: Module) -> Module {
+        m.visit_with(&mut PureFnCollector {
+            pure_fns: &mut self.pure_fns,
+        });
+        if self.config.remove_unused_vars {
+            self.usage = Some(collect_usage(&m));
+            self.in_poisoned_scope = has_eval_or_with(&m);
+        }
+
+        let m = m.fold_children(self);
+
+        if self.config.remove_unused_imports && !has_eval_or_with(&m) {
+            remove_unused_imports(m, self.config.keep_import_side_effects)
+        } else {
+            m
+        }
+    }
+}
+
+impl Fold<Script> for Remover {
+    fn fold(&mut self, s: Script) -> Script {
+        s.visit_with(&mut PureFnCollector {
+            pure_fns: &mut self.pure_fns,
+        });
+        if self.config.remove_unused_vars {
+            self.usage = Some(collect_usage(&s));
+            self.in_poisoned_scope = has_eval_or_with(&s);
+        }
+
+        self.in_global_scope = true;
+        let s = s.fold_children(self);
+        self.in_global_scope = false;
+
+        s
+    }
+}
+
+impl Fold<Function> for Remover {
+    fn fold(&mut self, f: Function) -> Function {
+        let old = replace(&mut self.in_global_scope, false);
+        let old_poisoned = replace(
+            &mut self.in_poisoned_scope,
+            self.usage.is_some() && has_eval_or_with(&f),
+        );
+        let f = f.fold_children(self);
+        self.in_global_scope = old;
+        self.in_poisoned_scope = old_poisoned;
+
+        f
+    }
+}
+
+impl Fold<ArrowExpr> for Remover {
+    fn fold(&mut self, f: ArrowExpr) -> ArrowExpr {
+        let old = replace(&mut self.in_global_scope, false);
+        let old_poisoned = replace(
+            &mut self.in_poisoned_scope,
+            self.usage.is_some() && has_eval_or_with(&f),
+        );
+        let f = f.fold_children(self);
+        self.in_global_scope = old;
+        self.in_poisoned_scope = old_poisoned;
+
+        f
+    }
 }
 
 impl<T: StmtLike> Fold<Vec<T>> for Remover
@@ -51,19 +219,30 @@ where
                         }) if is_block_stmt => continue,
 
                         // Control flow
-                        Stmt::Throw(..)
-                        | Stmt::Return { .. }
-                        | Stmt::Continue { .. }
-                        | Stmt::Break { .. } => {
-                            let decls: Vec<_> = iter
-                                .flat_map(|t| extract_var_ids(&t))
-                                .map(|i| VarDeclarator {
-                                    span: i.span,
-                                    name: Pat::Ident(i),
-                                    init: None,
-                                    definite: false,
-                                })
-                                .collect();
+                        _ if is_terminator(&stmt) => {
+                            // Code after a jump or an infinite loop is unreachable, but
+                            // declarations in it are hoisted.
+                            let mut decls = vec![];
+                            let mut hoisted = vec![];
+                            for t in iter {
+                                match t.try_into_stmt() {
+                                    Ok(Stmt::Decl(Decl::Fn(f))) => {
+                                        hoisted.push(T::from_stmt(Stmt::Decl(Decl::Fn(f))))
+                                    }
+                                    Ok(s) => {
+                                        decls.extend(extract_var_ids(&s).into_iter().map(|i| {
+                                            VarDeclarator {
+                                                span: i.span,
+                                                name: Pat::Ident(i),
+                                                init: None,
+                                                definite: false,
+                                            }
+                                        }))
+                                    }
+                                    // Imports and exports.
+                                    Err(t) => hoisted.push(t),
+                                }
+                            }
                             if !decls.is_empty() {
                                 buf.push(T::from_stmt(Stmt::Decl(Decl::Var(VarDecl {
                                     span: DUMMY_SP,
@@ -75,6 +254,7 @@ where
 
                             let stmt_like = T::from_stmt(stmt);
                             buf.push(stmt_like);
+                            buf.extend(hoisted.into_iter().map(|t| self.fold(t)));
 
                             return buf;
                         }
@@ -115,7 +295,7 @@ where
                             match test.as_bool() {
                                 (purity, Known(val)) => {
                                     if !purity.is_pure() {
-                                        let expr = ignore_result(*test);
+                                        let expr = self.ignore_result(*test);
 
                                         if let Some(expr) = expr {
                                             buf.push(T::from_stmt(Stmt::Expr(ExprStmt {
@@ -153,6 +333,47 @@ where
                             }
                         }
 
+                        // Remove unused variables.
+                        Stmt::Decl(Decl::Var(VarDecl {
+                            span,
+                            kind,
+                            declare,
+                            decls,
+                        })) if self.can_remove_vars() => {
+                            let mut kept = vec![];
+
+                            for decl in decls {
+                                if !self.is_unused(&decl.name) {
+                                    kept.push(decl);
+                                    continue;
+                                }
+
+                                // Preserve side effects of the initializer.
+                                if let Some(expr) = decl.init.and_then(|e| self.ignore_result(*e)) {
+                                    if !kept.is_empty() {
+                                        buf.push(T::from_stmt(Stmt::Decl(Decl::Var(VarDecl {
+                                            span,
+                                            kind,
+                                            declare,
+                                            decls: replace(&mut kept, vec![]),
+                                        }))));
+                                    }
+                                    buf.push(T::from_stmt(expr.into_stmt()));
+                                }
+                            }
+
+                            if kept.is_empty() {
+                                continue;
+                            }
+
+                            Stmt::Decl(Decl::Var(VarDecl {
+                                span,
+                                kind,
+                                declare,
+                                decls: kept,
+                            }))
+                        }
+
                         _ => stmt,
                     };
 
@@ -168,10 +389,30 @@ where
     }
 }
 
+impl Fold<LabeledStmt> for Remover {
+    fn fold(&mut self, s: LabeledStmt) -> LabeledStmt {
+        self.label = Some(s.label.sym.clone());
+
+        s.fold_children(self)
+    }
+}
+
 impl Fold<Stmt> for Remover {
     fn fold(&mut self, stmt: Stmt) -> Stmt {
+        let label = self.label.take();
         let stmt = stmt.fold_children(self);
 
+        let stmt = match stmt {
+            Stmt::Labeled(s) => {
+                if self.config.remove_unused_labels && !is_label_used(&s.body, &s.label.sym) {
+                    *s.body
+                } else {
+                    Stmt::Labeled(s)
+                }
+            }
+            _ => stmt,
+        };
+
         match stmt {
             Stmt::If(IfStmt {
                 span,
@@ -199,7 +440,7 @@ impl Fold<Stmt> for Remover {
                 if let (p, Known(v)) = test.as_bool() {
                     // Preserve effect of the test
                     if !p.is_pure() {
-                        match ignore_result(*test).map(Box::new) {
+                        match self.ignore_result(*test).map(Box::new) {
                             Some(expr) => stmts.push(Stmt::Expr(ExprStmt { span, expr })),
                             None => {}
                         }
@@ -235,7 +476,7 @@ impl Fold<Stmt> for Remover {
                 if alt.is_none() {
                     match *cons {
                         Stmt::Empty(..) => {
-                            if let Some(expr) = ignore_result(*test) {
+                            if let Some(expr) = self.ignore_result(*test) {
                                 return Stmt::Expr(ExprStmt {
                                     span,
                                     expr: box expr,
@@ -281,7 +522,7 @@ impl Fold<Stmt> for Remover {
                 span,
                 expr: box expr,
                 ..
-            }) => match ignore_result(expr) {
+            }) => match self.ignore_result(expr) {
                 Some(e) => Stmt::Expr(ExprStmt { span, expr: box e }),
                 None => Stmt::Empty(EmptyStmt { span: DUMMY_SP }),
             },
@@ -333,13 +574,10 @@ impl Fold<Stmt> for Remover {
             }
 
             Stmt::Switch(mut s) => {
+                // Callers should ensure that only top-level `break`s target the switch.
                 let remove_break = |stmts: Vec<Stmt>| {
-                    debug_assert!(
-                        !has_conditional_stopper(&*stmts) || has_unconditional_stopper(&*stmts)
-                    );
-
                     let mut done = false;
-                    stmts.move_flat_map(|s| {
+                    let stmts = stmts.move_flat_map(|s| {
                         if done {
                             match s {
                                 Stmt::Decl(Decl::Var(
@@ -375,7 +613,15 @@ impl Fold<Stmt> for Remover {
                             }
                             _ => Some(s),
                         }
-                    })
+                    });
+
+                    // A labeled `break` is fine, as the label is not removed.
+                    debug_assert!(
+                        !has_nested_break_to_switch(&stmts, None),
+                        "a nested `break` targets the removed switch statement"
+                    );
+
+                    stmts
                 };
 
                 let is_matching_literal = match *s.discriminant {
@@ -393,7 +639,7 @@ impl Fold<Stmt> for Remover {
 
                 // Remove empty switch
                 if s.cases.is_empty() {
-                    match ignore_result(*s.discriminant) {
+                    match self.ignore_result(*s.discriminant) {
                         Some(expr) => {
                             return Stmt::Expr(ExprStmt {
                                 span: s.span,
@@ -408,9 +654,10 @@ impl Fold<Stmt> for Remover {
                 if s.cases.len() == 1
                     && s.cases[0].test.is_none()
                     && !has_conditional_stopper(&s.cases[0].cons)
+                    && !has_nested_break_to_switch(&s.cases[0].cons, None)
                 {
                     let mut stmts = remove_break(s.cases.remove(0).cons);
-                    if let Some(expr) = ignore_result(*s.discriminant) {
+                    if let Some(expr) = self.ignore_result(*s.discriminant) {
                         prepend(&mut stmts, expr.into_stmt());
                     }
 
@@ -469,57 +716,83 @@ impl Fold<Stmt> for Remover {
 
                 let mut var_ids = vec![];
                 if let Some(i) = selected {
-                    if !has_conditional_stopper(&s.cases[i].cons) {
-                        let mut stmts = s.cases.remove(i).cons;
-                        let mut cases = s.cases.drain(i..);
+                    let mut cases = s.cases.split_off(i).into_iter();
+                    let selected = cases.next().unwrap();
 
+                    // Falls through until a stopper which is not nested in a condition.
+                    let mut stmts = selected.cons;
+                    if !has_top_level_stopper(&stmts) {
                         while let Some(case) = cases.next() {
-                            let should_stop = has_unconditional_stopper(&case.cons);
+                            let should_stop = has_top_level_stopper(&case.cons);
                             stmts.extend(case.cons);
-                            //
                             if should_stop {
                                 break;
                             }
                         }
+                    }
 
-                        let mut stmts = remove_break(stmts);
+                    // Cases before the selected one can't be matched.
+                    let decls = s
+                        .cases
+                        .drain(..)
+                        .chain(cases)
+                        .flat_map(|case| case.cons)
+                        .flat_map(|stmt| stmt.extract_var_ids())
+                        .map(|i| VarDeclarator {
+                            span: DUMMY_SP,
+                            name: Pat::Ident(i),
+                            init: None,
+                            definite: false,
+                        })
+                        .collect::<Vec<_>>();
+                    let decl = if decls.is_empty() {
+                        None
+                    } else {
+                        Some(Stmt::Decl(Decl::Var(VarDecl {
+                            span: DUMMY_SP,
+                            kind: VarDeclKind::Var,
+                            decls,
+                            declare: false,
+                        })))
+                    };
 
-                        let decls = cases
-                            .into_iter()
-                            .flat_map(|case| case.cons)
-                            .flat_map(|stmt| stmt.extract_var_ids())
-                            .map(|i| VarDeclarator {
-                                span: DUMMY_SP,
-                                name: Pat::Ident(i),
-                                init: None,
-                                definite: false,
-                            })
-                            .collect::<Vec<_>>();
+                    // We can't remove the switch statement, but we can remove other cases.
+                    if has_nested_break_to_switch(&stmts, label.as_ref()) {
+                        let s = Stmt::Switch(SwitchStmt {
+                            cases: vec![SwitchCase {
+                                cons: stmts,
+                                ..selected
+                            }],
+                            ..s
+                        });
 
-                        if !decls.is_empty() {
-                            prepend(
-                                &mut stmts,
-                                Stmt::Decl(Decl::Var(VarDecl {
-                                    span: DUMMY_SP,
-                                    kind: VarDeclKind::Var,
-                                    decls,
-                                    declare: false,
-                                })),
-                            );
-                        }
+                        return match decl {
+                            Some(decl) => Stmt::Block(BlockStmt {
+                                span: DUMMY_SP,
+                                stmts: vec![decl, s],
+                            }),
+                            None => s,
+                        };
+                    }
 
-                        return Stmt::Block(BlockStmt {
-                            span: s.span,
-                            stmts,
-                        })
-                        .fold_with(self);
+                    let mut stmts = remove_break(stmts);
+                    if let Some(decl) = decl {
+                        prepend(&mut stmts, decl);
                     }
+
+                    return Stmt::Block(BlockStmt {
+                        span: s.span,
+                        stmts,
+                    })
+                    .fold_with(self);
                 } else {
                     match *s.discriminant {
                         Expr::Lit(..) => {
                             let idx = s.cases.iter().position(|v| v.test.is_none());
                             if let Some(i) = idx {
-                                if !has_conditional_stopper(&s.cases[i].cons) {
+                                if !has_conditional_stopper(&s.cases[i].cons)
+                                    && !has_nested_break_to_switch(&s.cases[i].cons, None)
+                                {
                                     let stmts = s.cases.remove(i).cons;
                                     let stmts = remove_break(stmts);
 
@@ -595,6 +868,7 @@ impl Fold<Stmt> for Remover {
                     if is_default_last
                         && is_all_case_empty
                         && !has_conditional_stopper(&s.cases.last().unwrap().cons)
+                        && !has_nested_break_to_switch(&s.cases.last().unwrap().cons, None)
                     {
                         let stmts = s.cases.pop().unwrap().cons;
                         let stmts = remove_break(stmts);
@@ -655,43 +929,53 @@ impl Fold<Stmt> for Remover {
                     ..
                 },
             ) => {
-                let decl = s.body.extract_var_ids_as_var();
-                let body = if let Some(var) = decl {
-                    Stmt::Decl(Decl::Var(var))
-                } else {
-                    Stmt::Empty(EmptyStmt { span: s.span })
+                // Only the initializer is evaluated.
+                let init = match s.init {
+                    Some(VarDeclOrExpr::VarDecl(var)) => Some(Stmt::Decl(Decl::Var(var))),
+                    Some(VarDeclOrExpr::Expr(e)) => Some(e.into_stmt()),
+                    None => None,
                 };
-
-                if s.init.is_some() {
-                    Stmt::For(ForStmt {
-                        body: box body,
-                        update: None,
-                        ..s
-                    })
-                } else {
-                    body
+                let decl = s
+                    .body
+                    .extract_var_ids_as_var()
+                    .map(Decl::Var)
+                    .map(Stmt::Decl);
+
+                BlockStmt {
+                    span: s.span,
+                    stmts: init.into_iter().chain(decl).collect(),
                 }
+                .into()
             }
 
             Stmt::While(s) => {
                 if let (purity, Known(v)) = s.test.as_bool() {
                     if v {
-                        Stmt::While(WhileStmt {
-                            test: box Expr::Lit(Lit::Bool(Bool {
-                                span: s.test.span(),
-                                value: true,
-                            })),
-                            ..s
-                        })
-                    } else {
                         if purity.is_pure() {
-                            Stmt::Empty(EmptyStmt { span: s.span })
-                        } else {
                             Stmt::While(WhileStmt {
-                                body: box Stmt::Empty(EmptyStmt { span: s.span }),
+                                test: box Expr::Lit(Lit::Bool(Bool {
+                                    span: s.test.span(),
+                                    value: true,
+                                })),
                                 ..s
                             })
+                        } else {
+                            Stmt::While(s)
+                        }
+                    } else {
+                        // The test is evaluated once, and `var`s in the body are hoisted.
+                        let decl = s
+                            .body
+                            .extract_var_ids_as_var()
+                            .map(Decl::Var)
+                            .map(Stmt::Decl);
+                        let test = self.ignore_result(*s.test).map(|e| e.into_stmt());
+
+                        BlockStmt {
+                            span: s.span,
+                            stmts: decl.into_iter().chain(test).collect(),
                         }
+                        .into()
                     }
                 } else {
                     Stmt::While(s)
@@ -710,17 +994,24 @@ impl Fold<Stmt> for Remover {
                             body: s.body,
                         })
                     } else {
-                        if let Some(test) = ignore_result(*s.test) {
+                        let body = prepare_loop_body_for_inlining(*s.body);
+
+                        // These would target another statement if we remove the loop.
+                        if has_jump_to_loop(&body, label.as_ref()) {
+                            return Stmt::DoWhile(DoWhileStmt {
+                                body: box body,
+                                ..s
+                            });
+                        }
+
+                        if let Some(test) = self.ignore_result(*s.test) {
                             BlockStmt {
                                 span: s.span,
-                                stmts: vec![
-                                    prepare_loop_body_for_inlining(*s.body).fold_with(self),
-                                    test.into_stmt(),
-                                ],
+                                stmts: vec![body.fold_with(self), test.into_stmt()],
                             }
                             .into()
                         } else {
-                            prepare_loop_body_for_inlining(*s.body).fold_with(self)
+                            body.fold_with(self)
                         }
                     }
                 } else {
@@ -920,7 +1211,9 @@ impl Fold<SeqExpr> for Remover {
         }
 
         let last = e.exprs.pop().unwrap();
-        let mut exprs = e.exprs.move_flat_map(|e| ignore_result(*e).map(Box::new));
+        let mut exprs = e
+            .exprs
+            .move_flat_map(|e| self.ignore_result(*e).map(Box::new));
         exprs.push(last);
 
         SeqExpr { exprs, ..e }
@@ -994,10 +1287,13 @@ impl Fold<ForStmt> for Remover {
 
         ForStmt {
             init: s.init.and_then(|e| match e {
-                VarDeclOrExpr::Expr(e) => ignore_result(*e).map(Box::new).map(VarDeclOrExpr::from),
+                VarDeclOrExpr::Expr(e) => self
+                    .ignore_result(*e)
+                    .map(Box::new)
+                    .map(VarDeclOrExpr::from),
                 _ => Some(e),
             }),
-            update: s.update.and_then(|e| ignore_result(*e).map(Box::new)),
+            update: s.update.and_then(|e| self.ignore_result(*e).map(Box::new)),
             test: s.test.and_then(|e| {
                 let span = e.span();
                 if let Known(value) = e.as_pure_bool() {
@@ -1015,259 +1311,455 @@ impl Fold<ForStmt> for Remover {
     }
 }
 
-/// Ignores the result.
-///
-/// Returns
-///  - [Some] if `e` has a side effect.
-///  - [None] if `e` does not have a side effect.
-#[inline(never)]
-fn ignore_result(e: Expr) -> Option<Expr> {
-    match e {
-        Expr::Lit(Lit::Num(..))
-        | Expr::Lit(Lit::Bool(..))
-        | Expr::Lit(Lit::Null(..))
-        | Expr::Lit(Lit::Regex(..))
-        | Expr::Ident(..) => None,
-
-        Expr::Lit(Lit::Str(ref v)) if v.value.is_empty() => None,
-
-        Expr::Paren(ParenExpr { expr, .. }) => ignore_result(*expr),
-
-        Expr::Assign(AssignExpr {
-            op: op!("="),
-            left: PatOrExpr::Pat(box Pat::Ident(ref l)),
-            right: box Expr::Ident(r),
-            ..
-        }) if l.sym == r.sym && l.span.ctxt() == r.span.ctxt() => None,
-
-        Expr::Bin(BinExpr {
-            span,
-            left,
-            op,
-            right,
-        }) if op != op!("&&") && op != op!("||") => {
-            let left = ignore_result(*left);
-            let right = ignore_result(*right);
-
-            match (left, right) {
-                (Some(l), Some(r)) => {
-                    ignore_result(preserve_effects(span, *undefined(span), vec![box l, box r]))
-                }
-                (Some(l), None) => Some(l),
-                (None, Some(r)) => Some(r),
-                (None, None) => None,
-            }
+impl Remover {
+    fn can_remove_vars(&self) -> bool {
+        self.usage.is_some() && !self.in_global_scope && !self.in_poisoned_scope
+    }
+
+    /// Returns true if `name` is an identifier which is never referenced.
+    fn is_unused(&self, name: &Pat) -> bool {
+        match (name, &self.usage) {
+            (Pat::Ident(i), Some(usage)) => !usage.contains(&id(i)),
+            _ => false,
         }
+    }
 
-        Expr::Bin(BinExpr {
-            span,
-            left,
-            op,
-            right,
-        }) => {
-            if op == op!("&&") {
-                let right = if let Some(right) = ignore_result(*right) {
-                    box right
-                } else {
-                    return ignore_result(*left);
-                };
+    /// Returns true if `callee` refers to a function annotated with
+    /// `/*#__SIDE_EFFECT_FREE__*/`.
+    fn is_pure_fn(&self, callee: &Expr) -> bool {
+        match *callee {
+            Expr::Ident(ref i) => self.pure_fns.contains(&id(i)),
+            _ => false,
+        }
+    }
 
-                let l = left.as_pure_bool();
+    /// Ignores the result.
+    ///
+    /// Returns
+    ///  - [Some] if `e` has a side effect.
+    ///  - [None] if `e` does not have a side effect.
+    #[inline(never)]
+    fn ignore_result(&self, e: Expr) -> Option<Expr> {
+        match e {
+            Expr::Lit(Lit::Num(..))
+            | Expr::Lit(Lit::Bool(..))
+            | Expr::Lit(Lit::Null(..))
+            | Expr::Lit(Lit::Regex(..))
+            | Expr::Ident(..) => None,
 
-                if let Known(l) = l {
-                    Some(Expr::Lit(Lit::Bool(Bool { span, value: l })))
-                } else {
-                    Some(Expr::Bin(BinExpr {
+            Expr::Lit(Lit::Str(ref v)) if v.value.is_empty() => None,
+
+            Expr::Paren(ParenExpr { expr, .. }) => self.ignore_result(*expr),
+
+            Expr::Assign(AssignExpr {
+                op: op!("="),
+                left: PatOrExpr::Pat(box Pat::Ident(ref l)),
+                right: box Expr::Ident(r),
+                ..
+            }) if l.sym == r.sym && l.span.ctxt() == r.span.ctxt() => None,
+
+            Expr::Bin(BinExpr {
+                span,
+                left,
+                op,
+                right,
+            }) if op != op!("&&") && op != op!("||") => {
+                let left = self.ignore_result(*left);
+                let right = self.ignore_result(*right);
+
+                match (left, right) {
+                    (Some(l), Some(r)) => self.ignore_result(preserve_effects(
                         span,
-                        left,
-                        op,
-                        right,
-                    }))
+                        *undefined(span),
+                        vec![box l, box r],
+                    )),
+                    (Some(l), None) => Some(l),
+                    (None, Some(r)) => Some(r),
+                    (None, None) => None,
                 }
-            } else {
-                debug_assert_eq!(op, op!("||"));
+            }
 
-                let l = left.as_pure_bool();
+            Expr::Bin(BinExpr {
+                span,
+                left,
+                op,
+                right,
+            }) => {
+                if op == op!("&&") {
+                    let right = if let Some(right) = self.ignore_result(*right) {
+                        box right
+                    } else {
+                        return self.ignore_result(*left);
+                    };
 
-                if let Known(l) = l {
-                    if l {
-                        None
+                    let l = left.as_pure_bool();
+
+                    if let Known(l) = l {
+                        Some(Expr::Lit(Lit::Bool(Bool { span, value: l })))
                     } else {
-                        ignore_result(*right)
-                    }
-                } else {
-                    let right = ignore_result(*right);
-                    if let Some(right) = right {
                         Some(Expr::Bin(BinExpr {
                             span,
                             left,
                             op,
-                            right: box right,
+                            right,
                         }))
+                    }
+                } else {
+                    debug_assert_eq!(op, op!("||"));
+
+                    let l = left.as_pure_bool();
+
+                    if let Known(l) = l {
+                        if l {
+                            None
+                        } else {
+                            self.ignore_result(*right)
+                        }
                     } else {
-                        ignore_result(*left)
+                        let right = self.ignore_result(*right);
+                        if let Some(right) = right {
+                            Some(Expr::Bin(BinExpr {
+                                span,
+                                left,
+                                op,
+                                right: box right,
+                            }))
+                        } else {
+                            self.ignore_result(*left)
+                        }
                     }
                 }
             }
-        }
 
-        Expr::Unary(UnaryExpr { span, op, arg }) => match op {
-            op!("void")
-            | op!("typeof")
-            | op!(unary, "+")
-            | op!(unary, "-")
-            | op!("!")
-            | op!("~") => ignore_result(*arg),
-            _ => Some(Expr::Unary(UnaryExpr { span, op, arg })),
-        },
-
-        Expr::Array(ArrayLit { span, elems, .. }) => {
-            let mut has_spread = false;
-            let elems = elems.move_flat_map(|v| match v {
-                Some(ExprOrSpread {
-                    spread: Some(..), ..
-                }) => {
-                    has_spread = true;
-                    Some(v)
-                }
-                None => None,
-                Some(ExprOrSpread { spread: None, expr }) => ignore_result(*expr).map(|expr| {
+            Expr::Unary(UnaryExpr { span, op, arg }) => match op {
+                op!("void")
+                | op!("typeof")
+                | op!(unary, "+")
+                | op!(unary, "-")
+                | op!("!")
+                | op!("~") => self.ignore_result(*arg),
+                _ => Some(Expr::Unary(UnaryExpr { span, op, arg })),
+            },
+
+            Expr::Array(ArrayLit { span, elems, .. }) => {
+                let mut has_spread = false;
+                let elems = elems.move_flat_map(|v| match v {
                     Some(ExprOrSpread {
-                        spread: None,
-                        expr: box expr,
-                    })
-                }),
-            });
-
-            if elems.is_empty() {
-                None
-            } else {
-                if has_spread {
-                    Some(Expr::Array(ArrayLit { span, elems }))
+                        spread: Some(..), ..
+                    }) => {
+                        has_spread = true;
+                        Some(v)
+                    }
+                    None => None,
+                    Some(ExprOrSpread { spread: None, expr }) => {
+                        self.ignore_result(*expr).map(|expr| {
+                            Some(ExprOrSpread {
+                                spread: None,
+                                expr: box expr,
+                            })
+                        })
+                    }
+                });
+
+                if elems.is_empty() {
+                    None
                 } else {
-                    ignore_result(preserve_effects(
+                    if has_spread {
+                        Some(Expr::Array(ArrayLit { span, elems }))
+                    } else {
+                        self.ignore_result(preserve_effects(
+                            span,
+                            *undefined(span),
+                            elems.into_iter().map(|v| v.unwrap().expr),
+                        ))
+                    }
+                }
+            }
+
+            Expr::Object(ObjectLit { span, props, .. }) => {
+                let props = props.move_flat_map(|v| match v {
+                    PropOrSpread::Spread(..) => Some(v),
+                    PropOrSpread::Prop(ref p) => {
+                        if is_literal(&p) {
+                            None
+                        } else {
+                            Some(v)
+                        }
+                    }
+                });
+
+                if props.is_empty() {
+                    None
+                } else {
+                    self.ignore_result(preserve_effects(
                         span,
-                        *undefined(span),
-                        elems.into_iter().map(|v| v.unwrap().expr),
+                        *undefined(DUMMY_SP),
+                        once(box Expr::Object(ObjectLit { span, props })),
                     ))
                 }
             }
-        }
 
-        Expr::Object(ObjectLit { span, props, .. }) => {
-            let props = props.move_flat_map(|v| match v {
-                PropOrSpread::Spread(..) => Some(v),
-                PropOrSpread::Prop(ref p) => {
-                    if is_literal(&p) {
-                        None
-                    } else {
-                        Some(v)
-                    }
+            Expr::New(NewExpr {
+                span,
+                ref callee,
+                args,
+                ..
+            }) if callee.is_pure_callee() || has_annotation(span, "__PURE__") => self
+                .ignore_result(Expr::Array(ArrayLit {
+                    span,
+                    elems: args
+                        .map(|args| args.into_iter().map(Some).collect())
+                        .unwrap_or_else(Default::default),
+                })),
+
+            Expr::Call(CallExpr {
+                span,
+                callee: ExprOrSuper::Expr(ref callee),
+                args,
+                ..
+            }) if callee.is_pure_callee()
+                || self.is_pure_fn(callee)
+                || has_annotation(span, "__PURE__") =>
+            {
+                self.ignore_result(Expr::Array(ArrayLit {
+                    span,
+                    elems: args.into_iter().map(Some).collect(),
+                }))
+            }
+
+            Expr::Tpl(Tpl { span, exprs, .. }) => {
+                self.ignore_result(preserve_effects(span, *undefined(span), exprs))
+            }
+
+            Expr::TaggedTpl(TaggedTpl {
+                span, tag, exprs, ..
+            }) if tag.is_pure_callee() => {
+                self.ignore_result(preserve_effects(span, *undefined(span), exprs))
+            }
+
+            //
+            // Function expressions are useless if they are not used.
+            //
+            // As function expressions cannot start with 'function',
+            // this will be reached only if other things
+            // are removed while folding children.
+            Expr::Fn(..) => None,
+
+            Expr::Seq(SeqExpr {
+                span, mut exprs, ..
+            }) => {
+                if exprs.is_empty() {
+                    return None;
                 }
-            });
 
-            if props.is_empty() {
-                None
-            } else {
-                ignore_result(preserve_effects(
+                let last = self.ignore_result(*exprs.pop().unwrap()).map(Box::new);
+
+                exprs.extend(last);
+
+                Some(Expr::Seq(SeqExpr { span, exprs }))
+            }
+
+            Expr::Cond(CondExpr {
+                span,
+                test,
+                cons,
+                alt,
+            }) => {
+                let alt = if let Some(alt) = self.ignore_result(*alt) {
+                    alt
+                } else {
+                    return self.ignore_result(Expr::Bin(BinExpr {
+                        span,
+                        left: test,
+                        op: op!("&&"),
+                        right: cons,
+                    }));
+                };
+
+                let cons = if let Some(cons) = self.ignore_result(*cons) {
+                    cons
+                } else {
+                    return self.ignore_result(Expr::Bin(BinExpr {
+                        span,
+                        left: test,
+                        op: op!("||"),
+                        right: box alt,
+                    }));
+                };
+
+                Some(Expr::Cond(CondExpr {
                     span,
-                    *undefined(DUMMY_SP),
-                    once(box Expr::Object(ObjectLit { span, props })),
-                ))
+                    test,
+                    cons: box cons,
+                    alt: box alt,
+                }))
             }
+
+            _ => Some(e),
         }
+    }
+}
 
-        Expr::New(NewExpr {
-            span,
-            ref callee,
-            args,
-            ..
-        }) if callee.is_pure_callee() => ignore_result(Expr::Array(ArrayLit {
-            span,
-            elems: args
-                .map(|args| args.into_iter().map(Some).collect())
-                .unwrap_or_else(Default::default),
-        })),
-
-        Expr::Call(CallExpr {
-            span,
-            callee: ExprOrSuper::Expr(ref callee),
-            args,
-            ..
-        }) if callee.is_pure_callee() => ignore_result(Expr::Array(ArrayLit {
-            span,
-            elems: args.into_iter().map(Some).collect(),
-        })),
+/// Collects function declarations annotated with `/*#__SIDE_EFFECT_FREE__*/`.
+struct PureFnCollector<'a> {
+    pure_fns: &'a mut FxHashSet<Id>,
+}
+
+impl Visit<FnDecl> for PureFnCollector<'_> {
+    fn visit(&mut self, f: &FnDecl) {
+        f.visit_children(self);
 
-        Expr::Tpl(Tpl { span, exprs, .. }) => {
-            ignore_result(preserve_effects(span, *undefined(span), exprs))
+        if has_annotation(f.function.span, "__SIDE_EFFECT_FREE__") {
+            self.pure_fns.insert(id(&f.ident));
         }
+    }
+}
+
+impl Visit<ExportDecl> for PureFnCollector<'_> {
+    fn visit(&mut self, e: &ExportDecl) {
+        e.visit_children(self);
 
-        Expr::TaggedTpl(TaggedTpl {
-            span, tag, exprs, ..
-        }) if tag.is_pure_callee() => {
-            ignore_result(preserve_effects(span, *undefined(span), exprs))
+        match e.decl {
+            // `/*#__SIDE_EFFECT_FREE__*/ export function foo() {}`
+            Decl::Fn(ref f) if has_annotation(e.span, "__SIDE_EFFECT_FREE__") => {
+                self.pure_fns.insert(id(&f.ident));
+            }
+            _ => {}
         }
+    }
+}
+
+fn remove_unused_imports(mut m: Module, keep_side_effects: bool) -> Module {
+    let usage = collect_usage(&m);
+    let is_used = |i: &Ident| usage.contains(&id(i));
+
+    m.body = m.body.move_flat_map(|item| match item {
+        ModuleItem::ModuleDecl(ModuleDecl::Import(import)) => {
+            // `import 'foo'`
+            if import.specifiers.is_empty() {
+                return Some(ModuleItem::ModuleDecl(ModuleDecl::Import(import)));
+            }
 
-        //
-        // Function expressions are useless if they are not used.
-        //
-        // As function expressions cannot start with 'function',
-        // this will be reached only if other things
-        // are removed while folding children.
-        Expr::Fn(..) => None,
-
-        Expr::Seq(SeqExpr {
-            span, mut exprs, ..
-        }) => {
-            if exprs.is_empty() {
+            let specifiers: Vec<_> = import
+                .specifiers
+                .into_iter()
+                .filter(|s| match *s {
+                    ImportSpecifier::Specific(ImportSpecific { ref local, .. })
+                    | ImportSpecifier::Default(ImportDefault { ref local, .. })
+                    | ImportSpecifier::Namespace(ImportStarAs { ref local, .. }) => is_used(local),
+                })
+                .collect();
+
+            if specifiers.is_empty() && !keep_side_effects {
                 return None;
             }
 
-            let last = ignore_result(*exprs.pop().unwrap()).map(Box::new);
+            Some(ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
+                specifiers,
+                ..import
+            })))
+        }
+        _ => Some(item),
+    });
 
-            exprs.extend(last);
+    m
+}
 
-            Some(Expr::Seq(SeqExpr { span, exprs }))
+/// Collects referenced identifiers.
+fn collect_usage<T>(node: &T) -> FxHashSet<Id>
+where
+    T: VisitWith<UsageCollector>,
+{
+    let mut v = UsageCollector::default();
+    node.visit_with(&mut v);
+    v.usage
+}
+
+#[derive(Default)]
+struct UsageCollector {
+    usage: FxHashSet<Id>,
+}
+
+impl Visit<Ident> for UsageCollector {
+    fn visit(&mut self, i: &Ident) {
+        self.usage.insert(id(i));
+    }
+}
+
+impl Visit<ImportDecl> for UsageCollector {
+    // Bindings created by an import are not references.
+    fn visit(&mut self, _: &ImportDecl) {}
+}
+
+impl Visit<VarDeclarator> for UsageCollector {
+    fn visit(&mut self, d: &VarDeclarator) {
+        // The binding itself is not a reference.
+        match d.name {
+            Pat::Ident(..) => {}
+            _ => d.name.visit_with(self),
         }
 
-        Expr::Cond(CondExpr {
-            span,
-            test,
-            cons,
-            alt,
-        }) => {
-            let alt = if let Some(alt) = ignore_result(*alt) {
-                alt
-            } else {
-                return ignore_result(Expr::Bin(BinExpr {
-                    span,
-                    left: test,
-                    op: op!("&&"),
-                    right: cons,
-                }));
-            };
+        d.init.visit_with(self);
+    }
+}
 
-            let cons = if let Some(cons) = ignore_result(*cons) {
-                cons
-            } else {
-                return ignore_result(Expr::Bin(BinExpr {
-                    span,
-                    left: test,
-                    op: op!("||"),
-                    right: box alt,
-                }));
-            };
+impl Visit<MemberExpr> for UsageCollector {
+    fn visit(&mut self, e: &MemberExpr) {
+        e.obj.visit_with(self);
 
-            Some(Expr::Cond(CondExpr {
-                span,
-                test,
-                cons: box cons,
-                alt: box alt,
-            }))
+        if e.computed {
+            e.prop.visit_with(self);
+        }
+    }
+}
+
+impl Visit<PropName> for UsageCollector {
+    fn visit(&mut self, n: &PropName) {
+        if let PropName::Computed(..) = *n {
+            n.visit_children(self);
+        }
+    }
+}
+
+/// Returns true if `node` contains a direct call to `eval` or a `with`
+/// statement.
+///
+/// Nested functions are also searched, because those can access variables of
+/// `node`.
+fn has_eval_or_with<T>(node: &T) -> bool
+where
+    T: VisitWith<EvalFinder>,
+{
+    let mut v = EvalFinder { found: false };
+    node.visit_with(&mut v);
+    v.found
+}
+
+struct EvalFinder {
+    found: bool,
+}
+
+impl Visit<CallExpr> for EvalFinder {
+    fn visit(&mut self, e: &CallExpr) {
+        /// `(eval)(...)` is also a direct call.
+        fn is_eval(e: &Expr) -> bool {
+            match *e {
+                Expr::Ident(ref i) => i.sym == js_word!("eval"),
+                Expr::Paren(ParenExpr { ref expr, .. }) => is_eval(expr),
+                _ => false,
+            }
         }
 
-        _ => Some(e),
+        match e.callee {
+            ExprOrSuper::Expr(ref callee) if is_eval(callee) => self.found = true,
+            _ => e.visit_children(self),
+        }
+    }
+}
+
+impl Visit<WithStmt> for EvalFinder {
+    fn visit(&mut self, _: &WithStmt) {
+        self.found = true;
     }
 }
 
@@ -1383,6 +1875,180 @@ fn prepare_loop_body_for_inlining(stmt: Stmt) -> Stmt {
     BlockStmt { span, stmts }.into()
 }
 
+/// Returns true if `body` contains `break` or `continue` which targets the
+/// loop, which is labeled with `label`.
+fn has_jump_to_loop(body: &Stmt, label: Option<&JsWord>) -> bool {
+    has_jump_to(body, label, true)
+}
+
+/// Returns true if `s` contains `break` which targets the switch statement
+/// labeled with `label`.
+fn has_break_to_switch(s: &Stmt, label: Option<&JsWord>) -> bool {
+    has_jump_to(s, label, false)
+}
+
+/// Returns true if a `break` nested in `stmts` targets the switch statement
+/// labeled with `label`.
+fn has_nested_break_to_switch(stmts: &[Stmt], label: Option<&JsWord>) -> bool {
+    stmts.iter().any(|stmt| match *stmt {
+        Stmt::Break(BreakStmt { label: None, .. }) => false,
+        _ => has_break_to_switch(stmt, label),
+    })
+}
+
+fn has_jump_to(node: &Stmt, label: Option<&JsWord>, is_loop: bool) -> bool {
+    struct Visitor<'a> {
+        label: Option<&'a JsWord>,
+        is_loop: bool,
+        in_loop: bool,
+        in_switch: bool,
+        found: bool,
+    }
+
+    impl Visitor<'_> {
+        fn is_label(&self, l: &Option<Ident>) -> bool {
+            match (l, self.label) {
+                (Some(l), Some(label)) => l.sym == *label,
+                _ => false,
+            }
+        }
+    }
+
+    impl Visit<Function> for Visitor<'_> {
+        fn visit(&mut self, _: &Function) {}
+    }
+
+    impl Visit<Class> for Visitor<'_> {
+        fn visit(&mut self, _: &Class) {}
+    }
+
+    impl Visit<Stmt> for Visitor<'_> {
+        fn visit(&mut self, s: &Stmt) {
+            match *s {
+                Stmt::For(..)
+                | Stmt::ForIn(..)
+                | Stmt::ForOf(..)
+                | Stmt::While(..)
+                | Stmt::DoWhile(..) => {
+                    let old = replace(&mut self.in_loop, true);
+                    s.visit_children(self);
+                    self.in_loop = old;
+                }
+                Stmt::Switch(..) => {
+                    let old = replace(&mut self.in_switch, true);
+                    s.visit_children(self);
+                    self.in_switch = old;
+                }
+                _ => s.visit_children(self),
+            }
+        }
+    }
+
+    impl Visit<BreakStmt> for Visitor<'_> {
+        fn visit(&mut self, s: &BreakStmt) {
+            if self.is_label(&s.label) || (s.label.is_none() && !self.in_loop && !self.in_switch) {
+                self.found = true;
+            }
+        }
+    }
+
+    impl Visit<ContinueStmt> for Visitor<'_> {
+        fn visit(&mut self, s: &ContinueStmt) {
+            if self.is_label(&s.label) || (s.label.is_none() && self.is_loop && !self.in_loop) {
+                self.found = true;
+            }
+        }
+    }
+
+    let mut v = Visitor {
+        label,
+        is_loop,
+        in_loop: false,
+        in_switch: false,
+        found: false,
+    };
+    node.visit_with(&mut v);
+    v.found
+}
+
+/// Returns true if `body` contains `break` or `continue` which targets
+/// `label`.
+fn is_label_used(body: &Stmt, label: &JsWord) -> bool {
+    struct Visitor<'a> {
+        label: &'a JsWord,
+        found: bool,
+    }
+
+    impl Visit<Function> for Visitor<'_> {
+        fn visit(&mut self, _: &Function) {}
+    }
+
+    impl Visit<Class> for Visitor<'_> {
+        fn visit(&mut self, _: &Class) {}
+    }
+
+    impl Visit<BreakStmt> for Visitor<'_> {
+        fn visit(&mut self, s: &BreakStmt) {
+            if let Some(ref l) = s.label {
+                self.found |= l.sym == *self.label;
+            }
+        }
+    }
+
+    impl Visit<ContinueStmt> for Visitor<'_> {
+        fn visit(&mut self, s: &ContinueStmt) {
+            if let Some(ref l) = s.label {
+                self.found |= l.sym == *self.label;
+            }
+        }
+    }
+
+    let mut v = Visitor {
+        label,
+        found: false,
+    };
+    body.visit_with(&mut v);
+    v.found
+}
+
+/// Returns true if statements after `s` are unreachable.
+fn is_terminator(s: &Stmt) -> bool {
+    match *s {
+        Stmt::Throw(..) | Stmt::Return(..) | Stmt::Continue(..) | Stmt::Break(..) => true,
+        _ => is_infinite_loop(s, None),
+    }
+}
+
+/// Returns true if `s` is a loop which never exits normally.
+fn is_infinite_loop(s: &Stmt, label: Option<&JsWord>) -> bool {
+    let (test, body) = match *s {
+        // We don't track multiple labels.
+        Stmt::Labeled(LabeledStmt {
+            label: ref l,
+            ref body,
+            ..
+        }) if label.is_none() => return is_infinite_loop(body, Some(&l.sym)),
+
+        Stmt::While(WhileStmt {
+            ref test, ref body, ..
+        })
+        | Stmt::DoWhile(DoWhileStmt {
+            ref test, ref body, ..
+        }) => (Some(&**test), &**body),
+
+        Stmt::For(ForStmt {
+            ref test, ref body, ..
+        }) => (test.as_ref().map(|test| &**test), &**body),
+
+        _ => return false,
+    };
+
+    match test.map(|test| test.as_pure_bool()) {
+        None | Some(Known(true)) => !has_jump_to_loop(body, label),
+        _ => false,
+    }
+}
+
 fn has_unconditional_stopper(s: &[Stmt]) -> bool {
     check_for_stopper(s, false)
 }
@@ -1391,6 +2057,15 @@ fn has_conditional_stopper(s: &[Stmt]) -> bool {
     check_for_stopper(s, true)
 }
 
+/// Returns true if a statement of `s` is a jump, which makes statements after
+/// `s` unreachable.
+fn has_top_level_stopper(s: &[Stmt]) -> bool {
+    s.iter().any(|s| match *s {
+        Stmt::Break(..) | Stmt::Continue(..) | Stmt::Return(..) | Stmt::Throw(..) => true,
+        _ => false,
+    })
+}
+
 fn check_for_stopper(s: &[Stmt], only_conditional: bool) -> bool {
     struct Visitor {
         in_cond: bool,
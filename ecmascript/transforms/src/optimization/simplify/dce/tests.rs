@@ -1,4 +1,4 @@
-use super::dce;
+use super::{dce, dce_until_fixed_point, Config};
 use crate::optimization::expr_simplifier;
 use swc_common::chain;
 
@@ -6,7 +6,7 @@ macro_rules! test_stmt {
     ($l:expr, $r:expr) => {
         test_transform!(
             ::swc_ecma_parser::Syntax::default(),
-            |_| chain!(expr_simplifier(), dce()),
+            |_| chain!(expr_simplifier(), dce(Default::default())),
             $l,
             $r
         )
@@ -20,6 +20,15 @@ fn test(src: &str, expected: &str) {
     test_stmt!(src, expected)
 }
 
+fn test_with_config(config: Config, src: &str, expected: &str) {
+    test_transform!(
+        ::swc_ecma_parser::Syntax::default(),
+        |_| chain!(expr_simplifier(), dce(config)),
+        src,
+        expected
+    )
+}
+
 /// Should not modify expression.
 fn test_same(s: &str) {
     test(s, s)
@@ -44,6 +53,132 @@ fn usage() {
     test_stmt!("use(8+8);", "use(16);");
 }
 
+#[test]
+fn pure_annotation() {
+    test("/*#__PURE__*/ foo(); bar();", "bar();");
+    test("/*@__PURE__*/ foo(a(), 1);", "a();");
+    test("/*#__PURE__*/ new Foo(a());", "a();");
+    test("/*#__PURE__*/ new Foo;", "");
+    test_same("/* __PURE__ */ foo();");
+    test_same("foo(/*#__PURE__*/ bar());");
+}
+
+#[test]
+fn remove_unused_vars() {
+    let config = Config {
+        remove_unused_vars: true,
+        ..Default::default()
+    };
+
+    test_with_config(config, "var a = 1; use(b);", "use(b);");
+    test_with_config(
+        config,
+        "var a = 1, b = foo(), c = 2; use(c);",
+        "foo(); var c = 2; use(c);",
+    );
+    test_with_config(config, "let a = 1; a = 2;", "let a = 1; a = 2;");
+    test_with_config(config, "function f() { const a = {}; }", "function f() {}");
+    test_with_config(config, "var a = 1; eval('a');", "var a = 1; eval('a');");
+    test_with_config(
+        config,
+        "function f() { var a = 1; (() => eval('a'))(); }",
+        "function f() { var a = 1; (() => eval('a'))(); }",
+    );
+    test_with_config(
+        config,
+        "function f() { var a = 1; with (b) {} } function g() { var a = 1; }",
+        "function f() { var a = 1; with (b) {} } function g() {}",
+    );
+    test_with_config(
+        config,
+        "function f() { var a = 1; function g() { var b = 2; } eval('a'); }",
+        "function f() { var a = 1; function g() {} eval('a'); }",
+    );
+    test_with_config(
+        config,
+        "function f() { var a = 1; (0, eval)('a'); }",
+        "function f() { (0, eval)('a'); }",
+    );
+    test_with_config(
+        config,
+        "function f() { var a = 1; (eval)('a'); }",
+        "function f() { var a = 1; (eval)('a'); }",
+    );
+    test_with_config(
+        config,
+        "var a = 1; use(b.a, { a: 2 });",
+        "use(b.a, { a: 2 });",
+    );
+}
+
+#[test]
+fn remove_unused_imports() {
+    let config = Config {
+        remove_unused_imports: true,
+        ..Default::default()
+    };
+    test_with_config(config, "import a from 'a'; import { b, c } from 'b';", "");
+    test_with_config(
+        config,
+        "import a, * as b from 'a'; import { c, d as e } from 'b'; use(b, e);",
+        "import * as b from 'a'; import { d as e } from 'b'; use(b, e);",
+    );
+    test_with_config(config, "import 'a';", "import 'a';");
+    test_with_config(
+        config,
+        "import a from 'a'; export { a };",
+        "import a from 'a'; export { a };",
+    );
+    test_with_config(config, "import a from 'a'; if (false) { a(); }", "");
+    test_with_config(
+        config,
+        "import a from 'a'; eval('a');",
+        "import a from 'a'; eval('a');",
+    );
+    test_with_config(
+        Config {
+            keep_import_side_effects: true,
+            ..config
+        },
+        "import a from 'a'; import { b } from 'b'; b();",
+        "import 'a'; import { b } from 'b'; b();",
+    );
+}
+
+#[test]
+fn until_fixed_point() {
+    let config = Config {
+        remove_unused_vars: true,
+        ..Default::default()
+    };
+
+    test_transform!(
+        ::swc_ecma_parser::Syntax::default(),
+        |_| chain!(expr_simplifier(), dce(config)),
+        "var a = 1; var b = a; use(c);",
+        "var a = 1; use(c);"
+    );
+    test_transform!(
+        ::swc_ecma_parser::Syntax::default(),
+        |_| chain!(expr_simplifier(), dce_until_fixed_point(config)),
+        "var a = 1; var b = a; use(c);",
+        "use(c);"
+    );
+}
+
+#[test]
+fn side_effect_free_annotation() {
+    test(
+        "/*#__SIDE_EFFECT_FREE__*/ function foo() {} foo(1); bar();",
+        "function foo() {} bar();",
+    );
+    test(
+        "/*@__SIDE_EFFECT_FREE__*/ function foo() {} foo(bar());",
+        "function foo() {} bar();",
+    );
+    test_same("function foo() {} foo(1);");
+}
+
 #[test]
 fn compiled_out_simple() {
     compiled_out!(";");
@@ -207,6 +342,18 @@ fn test_constant_condition_with_side_effect1() {
     test("(b=0,b=1);if(b)x=b;", "b=0,b=1;if(b)x=b;");
     // test("b=1;if(foo,b)x=b;", "b=1;x=b;");
     test_same("b=1;if(foo=1,b)x=b;");
+    test(
+        "if (foo(), 0) { var a = 1; } else bar();",
+        "foo(); var a; bar();",
+    );
+    test(
+        "if (x = 1) foo(); else { var b = 2; }",
+        "x = 1; var b; foo();",
+    );
+    test(
+        "function f() { if (g(), true) return 1; else return 2; }",
+        "function f() { g(); return 1; }",
+    );
 }
 
 #[test]
@@ -251,6 +398,28 @@ fn test_fold_useless_for() {
     test("for(;false;) { foo(); continue }", "");
 }
 
+#[test]
+fn test_fold_useless_for_with_init() {
+    test("for(foo();false;) bar()", "foo()");
+    test("for(var i = foo();false;i++) bar()", "var i = foo()");
+    test(
+        "for(var i = 0;false;) { var a = bar(); }",
+        "var i = 0; var a;",
+    );
+    test(
+        "for(let i = foo();false;) bar(); let i;",
+        "{ let i = foo(); } let i;",
+    );
+}
+
+#[test]
+fn test_fold_useless_while() {
+    test("while(false) { foo() }", "");
+    test("while(0) { var a = foo() }", "var a");
+    test("while(foo(), false) bar()", "foo()");
+    test_same("while(foo(), true) bar()");
+}
+
 #[test]
 fn test_fold_useless_do_1() {
     test("do { foo() } while(false);", "foo()");
@@ -269,9 +438,11 @@ fn test_fold_useless_do_2() {
 
 #[test]
 fn test_fold_useless_do_3() {
+    test_same("do { try { foo() } catch (e) { break; } } while (0);");
+    test_same("l1: do { if (foo()) continue l1; bar(); } while (0);");
     test(
-        "do { try { foo() } catch (e) { break; } } while (0);",
-        "try { foo(); } catch (e) { break; }",
+        "do { for (;;) { if (foo()) break; } } while (0);",
+        "for (;;) { if (foo()) break; }",
     );
     test("do { foo(); break; } while(0)", "foo();");
     test(
@@ -472,7 +643,6 @@ fn test_optimize_switch_1() {
 }
 
 #[test]
-#[ignore]
 fn test_optimize_switch_2() {
     test(
         concat!(
@@ -502,7 +672,6 @@ fn test_optimize_switch_2() {
 }
 
 #[test]
-#[ignore]
 fn test_optimize_switch_3() {
     test(
         concat!(
@@ -517,6 +686,31 @@ fn test_optimize_switch_3() {
     );
 }
 
+#[test]
+fn test_optimize_switch_nested_break() {
+    test(
+        "switch (1) { case 0: var x = 1; case 1: foo(); break; case 2: bar(); }",
+        "var x; foo();",
+    );
+    test(
+        "switch (1) { case 2: bar(); case 1: try { foo() } finally { break } case 3: baz() }",
+        "switch (1) { case 1: try { foo() } finally { break } }",
+    );
+    test(
+        "a: switch (1) { case 1: for (;;) { break a; } }",
+        "a: switch (1) { case 1: for (;;) break a; }",
+    );
+    test(
+        "for (;;) { switch (1) { case 1: if (foo()) continue; bar(); } }",
+        "for (;;) { if (foo()) continue; bar(); }",
+    );
+    test_same("switch (a) { default: try { foo() } finally { break } }");
+    test(
+        "switch (1) { case 1: if (foo()) { break; } bar(); case 2: baz(); break; case 3: qux(); }",
+        "switch (1) { case 1: if (foo()) break; bar(); baz(); break; }",
+    );
+}
+
 #[test]
 fn test_optimize_switch_4() {
     test(
@@ -1227,6 +1421,29 @@ fn test_no_remove_inherits4() {
     );
 }
 
+#[test]
+fn remove_unused_labels() {
+    let config = Config {
+        remove_unused_labels: true,
+        ..Default::default()
+    };
+
+    test_with_config(config, "a: for (;;) foo();", "for (;;) foo();");
+    test_with_config(config, "a: { foo(); bar(); }", "foo(); bar();");
+    test_with_config(config, "a: { let x = foo(); }", "{ let x = foo(); }");
+    test_with_config(config, "a: b: for (;;) break b;", "b: for (;;) break b;");
+    test_with_config(
+        config,
+        "a: for (;;) { (function () { a: for (;;) break a; })(); }",
+        "for (;;) (function () { a: for (;;) break a; })();",
+    );
+    test_with_config(
+        config,
+        "a: for (;;) { for (;;) { if (foo()) continue a; bar(); } }",
+        "a: for (;;) for (;;) { if (foo()) continue a; bar(); }",
+    );
+}
+
 #[test]
 fn test_remove_from_label1() {
     test("LBL: void 0", "");
@@ -1302,6 +1519,13 @@ fn test_tagged_template_lit_substituting_template() {
     test("Math.sin`Complex ${andDangerous()}`", "andDangerous()");
 }
 
+#[test]
+fn test_tagged_template_lit_string_raw() {
+    test("String.raw`Complex ${butSafe}`", "");
+    test("String.raw`Complex ${andDangerous()}`", "andDangerous()");
+    test_same("String.foo`Complex ${butSafe}`");
+}
+
 #[test]
 fn test_fold_assign() {
     test("x=x", "");
@@ -1312,6 +1536,40 @@ fn test_fold_assign() {
     test("y=1 + (x=x)", "y=1 + x");
 }
 
+#[test]
+fn test_hoisted_decls_after_jump() {
+    test(
+        "function f() { return g(); function g() { return 1; } foo(); }",
+        "function f() { return g(); function g() { return 1; } }",
+    );
+    test(
+        "function f() { throw 1; var x = 2; function g() {} }",
+        "function f() { var x; throw 1; function g() {} }",
+    );
+    test(
+        "for (;;) { break; function g() {} var y; }",
+        "for (;;) { var y; break; function g() {} }",
+    );
+}
+
+#[test]
+fn test_infinite_loop() {
+    test("for (;;) foo(); bar();", "for (;;) foo();");
+    test("while (true) foo(); bar();", "while (true) foo();");
+    test(
+        "function f() { for (;;) { foo(); } var x = 1; function g() {} }",
+        "function f() { var x; for (;;) foo(); function g() {} }",
+    );
+    test(
+        "l: for (;;) { for (;;) { break; } } bar();",
+        "l: for (;;) for (;;) break;",
+    );
+    test_same("for (;;) { if (foo()) break; } bar();");
+    test_same("for (;;) { switch (foo()) { default: continue; } } bar();");
+    test_same("l: for (;;) { for (;;) { break l; } } bar();");
+    test_same("while (x) foo(); bar();");
+}
+
 #[test]
 fn test_try_catch_finally() {
     test_same("try {foo()} catch (e) {bar()}");
@@ -24,6 +24,56 @@ impl Fold<Pat> for SimplifyExpr {
     }
 }
 
+impl Fold<PatOrExpr> for SimplifyExpr {
+    fn fold(&mut self, n: PatOrExpr) -> PatOrExpr {
+        match n {
+            // The target of an assignment should remain a reference.
+            PatOrExpr::Expr(box Expr::Member(e)) => {
+                PatOrExpr::Expr(box Expr::Member(fold_computed_prop(e.fold_children(self))))
+            }
+            PatOrExpr::Pat(box Pat::Expr(box Expr::Member(e))) => PatOrExpr::Pat(box Pat::Expr(
+                box Expr::Member(fold_computed_prop(e.fold_children(self))),
+            )),
+            _ => n.fold_children(self),
+        }
+    }
+}
+
+impl Fold<UpdateExpr> for SimplifyExpr {
+    fn fold(&mut self, e: UpdateExpr) -> UpdateExpr {
+        let arg = match *e.arg {
+            // The operand of `++` and `--` should remain a reference.
+            Expr::Member(m) => box Expr::Member(fold_computed_prop(m.fold_children(self))),
+            arg => box arg.fold_with(self),
+        };
+
+        UpdateExpr { arg, ..e }
+    }
+}
+
+impl Fold<PropName> for SimplifyExpr {
+    fn fold(&mut self, n: PropName) -> PropName {
+        match n.fold_children(self) {
+            // `{['a' + 'b']: 1}` => `{ab: 1}`
+            PropName::Computed(ComputedPropName { expr, span }) => {
+                match computed_key_ident(&expr) {
+                    // These have special meaning if not computed.
+                    Some(ref i)
+                        if &*i.sym == "__proto__"
+                            || &*i.sym == "constructor"
+                            || &*i.sym == "prototype" =>
+                    {
+                        PropName::Computed(ComputedPropName { expr, span })
+                    }
+                    Some(i) => PropName::Ident(i),
+                    None => PropName::Computed(ComputedPropName { expr, span }),
+                }
+            }
+            n => n,
+        }
+    }
+}
+
 impl Fold<Expr> for SimplifyExpr {
     fn fold(&mut self, expr: Expr) -> Expr {
         // fold children before doing something more.
@@ -58,6 +108,17 @@ impl Fold<Expr> for SimplifyExpr {
                         })
                     }
                 }
+                // `c ? x : x` => `(c, x)`
+                _ if is_same_value(&cons, &alt) => {
+                    if test.may_have_side_effects() {
+                        Expr::Seq(SeqExpr {
+                            span,
+                            exprs: vec![test, cons],
+                        })
+                    } else {
+                        *cons
+                    }
+                }
                 _ => Expr::Cond(CondExpr {
                     span,
                     test,
@@ -120,6 +181,38 @@ impl Fold<Expr> for SimplifyExpr {
                 ObjectLit { span, props: ps }.into()
             }
 
+            // `a${1}b` => 'a1b'
+            Expr::Tpl(..) => {
+                let value = match expr.as_string() {
+                    Known(value) if !expr.may_have_side_effects() => Some(value.into_owned()),
+                    _ => None,
+                };
+
+                match value {
+                    Some(value) => str_lit(expr.span(), value),
+                    None => expr,
+                }
+            }
+
+            Expr::TaggedTpl(e) => match eval_string_raw(&e) {
+                Known(v) => v,
+                Unknown => Expr::TaggedTpl(e),
+            },
+
+            Expr::Call(e) => {
+                if let Known(v) = eval_cast(&e) {
+                    return v;
+                }
+                if let Known(v) = eval_string_method(&e) {
+                    return v;
+                }
+
+                match eval_math_call(&e) {
+                    Known(v) => v,
+                    Unknown => Expr::Call(e),
+                }
+            }
+
             Expr::New(e) => {
                 if e.callee.is_ident_ref_to(js_word!("String"))
                     && e.args.is_some()
@@ -147,7 +240,45 @@ impl Fold<Expr> for SimplifyExpr {
     }
 }
 
+/// Returns an identifier with the name `e` evaluates to, if the name is a
+/// valid identifier.
+fn computed_key_ident(e: &Expr) -> Option<Ident> {
+    if e.may_have_side_effects() {
+        return None;
+    }
+
+    match e.as_string() {
+        Known(s) => {
+            let sym = JsWord::from(&*s);
+            if is_valid_ident(&sym) {
+                Some(Ident::new(sym, e.span()))
+            } else {
+                None
+            }
+        }
+        Unknown => None,
+    }
+}
+
+/// `obj['ab']` => `obj.ab`
+fn fold_computed_prop(e: MemberExpr) -> MemberExpr {
+    if !e.computed {
+        return e;
+    }
+
+    match computed_key_ident(&e.prop) {
+        Some(i) => MemberExpr {
+            prop: box Expr::Ident(i),
+            computed: false,
+            ..e
+        },
+        None => e,
+    }
+}
+
 fn fold_member_expr(e: MemberExpr) -> Expr {
+    let e = fold_computed_prop(e);
+
     #[derive(Clone, PartialEq, Eq)]
     enum KnownOp {
         /// [a, b].length
@@ -162,8 +293,13 @@ fn fold_member_expr(e: MemberExpr) -> Expr {
         Expr::Ident(Ident {
             sym: js_word!("length"),
             ..
-        }) => KnownOp::Len,
-        Expr::Ident(Ident { ref sym, .. }) => KnownOp::IndexStr(sym.clone()),
+        }) if !e.computed => KnownOp::Len,
+        Expr::Ident(Ident { ref sym, .. }) if !e.computed => KnownOp::IndexStr(sym.clone()),
+        Expr::Lit(Lit::Str(Str {
+            value: js_word!("length"),
+            ..
+        })) => KnownOp::Len,
+        Expr::Lit(Lit::Str(Str { ref value, .. })) => KnownOp::IndexStr(value.clone()),
         Expr::Lit(Lit::Num(Number { value, .. })) if value.fract() == 0.0 => {
             KnownOp::Index(value as _)
         }
@@ -180,26 +316,33 @@ fn fold_member_expr(e: MemberExpr) -> Expr {
             ref value, span, ..
         })) => match op {
             // 'foo'.length
+            //
+            // Strings are indexed by utf-16 code units.
             KnownOp::Len => Expr::Lit(Lit::Num(Number {
-                value: value.chars().count() as f64,
+                value: value.encode_utf16().count() as f64,
                 span,
             })),
 
+            // 'foo'[5]
+            KnownOp::Index(idx) if idx < 0 || idx as usize >= value.encode_utf16().count() => {
+                *undefined(span)
+            }
+
             // 'foo'[1]
-            KnownOp::Index(idx) if (idx as usize) < value.len() => {
-                return if idx < 0 {
-                    *undefined(span)
-                } else {
-                    Expr::Lit(Lit::Str(Str {
-                        value: value
-                            .chars()
-                            .nth(idx as _)
-                            .unwrap_or_else(|| panic!("failed to index char?"))
-                            .to_string()
-                            .into(),
+            KnownOp::Index(idx) => {
+                let unit = value.encode_utf16().nth(idx as _).unwrap();
+
+                // A half of a surrogate pair cannot be represented.
+                match std::char::from_u32(u32::from(unit)) {
+                    Some(c) => Expr::Lit(Lit::Str(Str {
+                        value: c.to_string().into(),
                         span,
                         has_escape: false,
-                    }))
+                    })),
+                    None => Expr::Member(MemberExpr {
+                        obj: ExprOrSuper::Expr(box obj),
+                        ..e
+                    }),
                 }
             }
             _ => Expr::Member(MemberExpr {
@@ -272,7 +415,8 @@ fn fold_member_expr(e: MemberExpr) -> Expr {
 
         // { foo: true }['foo']
         Expr::Object(ObjectLit { mut props, span }) => match op {
-            KnownOp::IndexStr(key) if is_literal(&props) => {
+            // `__proto__` is not an own property.
+            KnownOp::IndexStr(key) if &*key != "__proto__" && is_literal(&props) => {
                 // do nothing if spread exists
                 let has_spread = props.iter().any(|prop| match prop {
                     PropOrSpread::Spread(..) => true,
@@ -286,7 +430,7 @@ fn fold_member_expr(e: MemberExpr) -> Expr {
                     });
                 }
 
-                let idx = props.iter().rev().position(|p| match &*p {
+                let idx = props.iter().rposition(|p| match &*p {
                     PropOrSpread::Prop(p) => match &**p {
                         Prop::Shorthand(i) => i.sym == key,
                         Prop::KeyValue(k) => prop_name_eq(&k.key, &key),
@@ -321,11 +465,11 @@ fn fold_member_expr(e: MemberExpr) -> Expr {
                             once(box Expr::Object(ObjectLit { props, span })),
                         )
                     }
-                    None => preserve_effects(
-                        span,
-                        *undefined(span),
-                        once(box Expr::Object(ObjectLit { props, span })),
-                    ),
+                    // The property may exist on the prototype.
+                    None => Expr::Member(MemberExpr {
+                        obj: ExprOrSuper::Expr(box Expr::Object(ObjectLit { props, span })),
+                        ..e
+                    }),
                 }
             }
             _ => Expr::Member(MemberExpr {
@@ -341,6 +485,370 @@ fn fold_member_expr(e: MemberExpr) -> Expr {
     }
 }
 
+/// Evaluates `String.raw` on a template literal, e.g.
+///
+/// String.raw`a\n${1}` --> 'a\\n1'
+fn eval_string_raw(e: &TaggedTpl) -> Value<Expr> {
+    /// Cooks `raw` again, if it only contains simple escapes.
+    ///
+    /// The lexer does not preserve some of raw strings (e.g. `\x41` and line
+    /// continuations) as is, so we compare the result with the cooked string.
+    fn cook(raw: &str) -> Option<String> {
+        let mut buf = String::with_capacity(raw.len());
+        let mut chars = raw.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                // Line terminators are normalized in raw strings.
+                '\r' => return None,
+                '\\' => buf.push(match chars.next()? {
+                    'n' => '\n',
+                    'r' => '\r',
+                    't' => '\t',
+                    'b' => '\u{0008}',
+                    'v' => '\u{000b}',
+                    'f' => '\u{000c}',
+                    c @ '\\' | c @ '`' | c @ '$' | c @ '\'' | c @ '"' => c,
+                    _ => return None,
+                }),
+                _ => buf.push(c),
+            }
+        }
+        Some(buf)
+    }
+
+    match *e.tag {
+        Expr::Member(MemberExpr {
+            obj: ExprOrSuper::Expr(ref obj),
+            prop: box Expr::Ident(Ident { ref sym, .. }),
+            computed: false,
+            ..
+        }) if obj.is_ident_ref_to(js_word!("String")) && &**sym == "raw" => {}
+        _ => return Unknown,
+    }
+    if e.type_params.is_some() {
+        return Unknown;
+    }
+
+    let mut buf = String::new();
+    for (i, quasi) in e.quasis.iter().enumerate() {
+        let cooked = match quasi.cooked {
+            Some(ref cooked) => &cooked.value,
+            None => return Unknown,
+        };
+        if cook(&quasi.raw.value).as_ref().map(|s| &**s) != Some(&**cooked) {
+            return Unknown;
+        }
+        buf.push_str(&quasi.raw.value);
+
+        if let Some(expr) = e.exprs.get(i) {
+            if expr.may_have_side_effects() {
+                return Unknown;
+            }
+            buf.push_str(&expr.as_string()?);
+        }
+    }
+
+    Known(str_lit(e.span, buf))
+}
+
+/// Evaluates a call to a primitive constructor, e.g.
+///
+/// String(1) --> '1'
+///
+/// Number('1') --> 1
+///
+/// Boolean((foo(), 1)) --> (foo(), true)
+fn eval_cast(e: &CallExpr) -> Value<Expr> {
+    let span = e.span;
+
+    let callee = match e.callee {
+        ExprOrSuper::Expr(ref callee) => callee,
+        _ => return Unknown,
+    };
+    let arg = match *e.args {
+        [] => None,
+        [ExprOrSpread {
+            spread: None,
+            ref expr,
+        }] => Some(expr),
+        _ => return Unknown,
+    };
+
+    if callee.is_ident_ref_to(js_word!("Boolean")) {
+        return Known(match arg {
+            None => make_bool_expr(span, false, iter::empty()),
+            Some(arg) => make_bool_expr(span, arg.as_bool().1?, once(arg.clone())),
+        });
+    }
+
+    if arg.map(|arg| arg.may_have_side_effects()).unwrap_or(false) {
+        return Unknown;
+    }
+
+    if callee.is_ident_ref_to(js_word!("String")) {
+        let value = match arg {
+            None => String::new(),
+            Some(arg) => arg.as_string()?.into_owned(),
+        };
+        return Known(str_lit(span, value));
+    }
+
+    if callee.is_ident_ref_to(js_word!("Number")) {
+        let value = match arg {
+            None => 0.0,
+            Some(arg) => arg.as_number()?,
+        };
+        if NumEdgeCase::of(value).is_some() {
+            return Unknown;
+        }
+        return Known(num_lit(span, value));
+    }
+
+    Unknown
+}
+
+/// Evaluates a call to a method of `String.prototype` on a string literal, e.g.
+///
+/// 'abc'.charAt(1) --> 'b'
+///
+/// 'a,b'.split(',') --> ['a', 'b']
+fn eval_string_method(e: &CallExpr) -> Value<Expr> {
+    let span = e.span;
+
+    let (value, method) = match e.callee {
+        ExprOrSuper::Expr(box Expr::Member(MemberExpr {
+            obj: ExprOrSuper::Expr(box Expr::Lit(Lit::Str(Str { ref value, .. }))),
+            prop: box Expr::Ident(Ident { ref sym, .. }),
+            computed: false,
+            ..
+        })) if PURE_STRING_METHODS.contains(&&**sym) => (value, sym),
+        _ => return Unknown,
+    };
+
+    let mut args = Vec::with_capacity(e.args.len());
+    for arg in &e.args {
+        match *arg {
+            ExprOrSpread {
+                spread: None,
+                ref expr,
+            } => match **expr {
+                Expr::Lit(Lit::Str(..)) | Expr::Lit(Lit::Num(..)) => args.push(&**expr),
+                _ => return Unknown,
+            },
+            _ => return Unknown,
+        }
+    }
+
+    // Strings are indexed by utf-16 code units.
+    let units = value.encode_utf16().collect::<Vec<_>>();
+    let len = units.len();
+
+    // `ToIntegerOrInfinity(arg)`, or `default` if it's not passed.
+    let int_arg = |i: usize, default: f64| -> Value<f64> {
+        match args.get(i) {
+            Some(arg) => {
+                let v = arg.as_number()?;
+                Known(if v.is_nan() { 0.0 } else { v.trunc() })
+            }
+            None => Known(default),
+        }
+    };
+    let clamp = |v: f64| -> usize {
+        if v <= 0.0 {
+            0
+        } else if v >= len as f64 {
+            len
+        } else {
+            v as usize
+        }
+    };
+    let substr = |start: usize, end: usize| -> Value<Expr> {
+        match String::from_utf16(&units[start..end.max(start)]) {
+            Ok(s) => Known(str_lit(span, s)),
+            // A half of a surrogate pair cannot be represented.
+            Err(..) => Unknown,
+        }
+    };
+    let find = |needle: &str, from: usize, rev: bool| -> f64 {
+        let needle = needle.encode_utf16().collect::<Vec<_>>();
+        let mut candidates = from..=len.saturating_sub(needle.len());
+        let pos = if rev {
+            candidates.rfind(|&i| units[i..].starts_with(&needle))
+        } else {
+            candidates.find(|&i| units[i..].starts_with(&needle))
+        };
+        pos.map(|i| i as f64).unwrap_or(-1.0)
+    };
+
+    Known(match &**method {
+        "charAt" => {
+            let idx = int_arg(0, 0.0)?;
+            if idx < 0.0 || idx >= len as f64 {
+                str_lit(span, String::new())
+            } else {
+                substr(idx as usize, idx as usize + 1)?
+            }
+        }
+
+        "charCodeAt" => {
+            let idx = int_arg(0, 0.0)?;
+            if idx < 0.0 || idx >= len as f64 {
+                // NaN
+                return Unknown;
+            }
+            num_lit(span, f64::from(units[idx as usize]))
+        }
+
+        "indexOf" => {
+            let needle = args.get(0).map(|arg| arg.as_string()).unwrap_or(Unknown)?;
+            num_lit(span, find(&needle, clamp(int_arg(1, 0.0)?), false))
+        }
+
+        "lastIndexOf" if args.len() <= 1 => {
+            let needle = args.get(0).map(|arg| arg.as_string()).unwrap_or(Unknown)?;
+            num_lit(span, find(&needle, 0, true))
+        }
+
+        "slice" => {
+            let relative = |v: f64| {
+                if v < 0.0 {
+                    clamp(len as f64 + v)
+                } else {
+                    clamp(v)
+                }
+            };
+            substr(
+                relative(int_arg(0, 0.0)?),
+                relative(int_arg(1, len as f64)?),
+            )?
+        }
+
+        "substring" => {
+            let (start, end) = (clamp(int_arg(0, 0.0)?), clamp(int_arg(1, len as f64)?));
+            substr(start.min(end), start.max(end))?
+        }
+
+        "toLowerCase" if value.is_ascii() => str_lit(span, value.to_ascii_lowercase()),
+        "toUpperCase" if value.is_ascii() => str_lit(span, value.to_ascii_uppercase()),
+
+        "split" if args.len() <= 1 => {
+            let parts: Vec<String> = match args.get(0) {
+                None => vec![value.to_string()],
+                Some(sep) => {
+                    let sep = sep.as_string()?;
+                    if !sep.is_empty() {
+                        value.split(&*sep).map(String::from).collect()
+                    } else if units.len() == value.chars().count() {
+                        value.chars().map(String::from).collect()
+                    } else {
+                        // A half of a surrogate pair cannot be represented.
+                        return Unknown;
+                    }
+                }
+            };
+
+            Expr::Array(ArrayLit {
+                span,
+                elems: parts
+                    .into_iter()
+                    .map(|s| {
+                        Some(ExprOrSpread {
+                            spread: None,
+                            expr: box str_lit(span, s),
+                        })
+                    })
+                    .collect(),
+            })
+        }
+
+        _ => return Unknown,
+    })
+}
+
+/// Evaluates a call to a deterministic method of `Math`, e.g.
+///
+/// Math.pow(2, 10) --> 1024
+///
+/// Math.max(1, 2) --> 2
+fn eval_math_call(e: &CallExpr) -> Value<Expr> {
+    let method = match e.callee {
+        ExprOrSuper::Expr(box Expr::Member(MemberExpr {
+            obj: ExprOrSuper::Expr(ref obj),
+            prop: box Expr::Ident(Ident { ref sym, .. }),
+            computed: false,
+            ..
+        })) if obj.is_ident_ref_to(js_word!("Math")) => sym,
+        _ => return Unknown,
+    };
+
+    let mut args = Vec::with_capacity(e.args.len());
+    for arg in &e.args {
+        if arg.spread.is_some() || arg.expr.may_have_side_effects() {
+            return Unknown;
+        }
+
+        let v = arg.expr.as_number()?;
+        // `Math.min(0, -0)` is `-0`, but `f64::min` may return either of them.
+        if v.is_nan() || (v == 0.0 && v.is_sign_negative()) {
+            return Unknown;
+        }
+        args.push(v);
+    }
+
+    let (x, y) = (args.get(0).cloned(), args.get(1).cloned());
+    let v = match (&**method, x, y) {
+        ("abs", Some(x), _) => x.abs(),
+        ("ceil", Some(x), _) => x.ceil(),
+        ("floor", Some(x), _) => x.floor(),
+        // `f64::round` rounds half away from zero, while `Math.round` rounds half up.
+        ("round", Some(x), _) => {
+            let r = x.floor();
+            if x - r < 0.5 {
+                r
+            } else if x < 0.0 && r == -1.0 {
+                // `-0`
+                return Unknown;
+            } else {
+                r + 1.0
+            }
+        }
+        ("sign", Some(x), _) => {
+            if x > 0.0 {
+                1.0
+            } else if x < 0.0 {
+                -1.0
+            } else {
+                x
+            }
+        }
+        ("trunc", Some(x), _) => x.trunc(),
+        ("max", Some(..), _) => args.iter().cloned().fold(std::f64::NEG_INFINITY, f64::max),
+        ("min", Some(..), _) => args.iter().cloned().fold(std::f64::INFINITY, f64::min),
+        // Results of `Math.pow` are exact only for integers.
+        ("pow", Some(x), Some(y)) if x.fract() == 0.0 && y.fract() == 0.0 && y >= 0.0 => x.powf(y),
+        _ => return Unknown,
+    };
+
+    // e.g. `Math.round(-0.1)` is `-0`
+    if NumEdgeCase::of(v).is_some() {
+        return Unknown;
+    }
+
+    Known(num_lit(e.span, v))
+}
+
+fn str_lit(span: Span, value: String) -> Expr {
+    Expr::Lit(Lit::Str(Str {
+        span,
+        value: value.into(),
+        has_escape: false,
+    }))
+}
+
+fn num_lit(span: Span, value: f64) -> Expr {
+    Expr::Lit(Lit::Num(Number { span, value }))
+}
+
 fn fold_bin(
     BinExpr {
         left,
@@ -418,6 +926,50 @@ fn fold_bin(
                             }
                         }
 
+                        // `x + 'a' + 'b'` => `x + 'ab'`
+                        //
+                        // This is valid only if both `x + 'a'` and `'a' + 'b'` are string
+                        // concatenations.
+                        let merged = match *left {
+                            Expr::Bin(BinExpr {
+                                op: op!(bin, "+"),
+                                right: ref mid,
+                                ..
+                            }) => match left.get_type() {
+                                Known(StringType)
+                                    if (mid.is_str() || right.is_str())
+                                        && !mid.may_have_side_effects()
+                                        && !right.may_have_side_effects() =>
+                                {
+                                    match (mid.as_string(), right.as_string()) {
+                                        (Known(l), Known(r)) => Some(format!("{}{}", l, r)),
+                                        _ => None,
+                                    }
+                                }
+                                _ => None,
+                            },
+                            _ => None,
+                        };
+
+                        if let Some(value) = merged {
+                            let left = match *left {
+                                Expr::Bin(BinExpr { left, .. }) => left,
+                                _ => unreachable!(),
+                            };
+
+                            return Expr::Bin(BinExpr {
+                                span,
+                                left,
+                                op,
+                                right: box Expr::Lit(Lit::Str(Str {
+                                    span: right.span(),
+                                    value: value.into(),
+                                    // TODO
+                                    has_escape: false,
+                                })),
+                            });
+                        }
+
                         return Expr::Bin(BinExpr {
                             left,
                             op,
@@ -453,7 +1005,6 @@ fn fold_bin(
                 _ => {}
             }
 
-            //TODO: try string concat
             return bin;
         }
 
@@ -486,7 +1037,37 @@ fn fold_bin(
                     Expr::Seq(seq)
                 };
             }
-            _ => (left, right),
+            _ => {
+                // `(a || false) || b` => `a || b`
+                // `(a && true) && b` => `a && b`
+                let is_useless_right = match *left {
+                    Expr::Bin(BinExpr {
+                        op: left_op,
+                        right: ref left_rhs,
+                        ..
+                    }) if left_op == op => match left_rhs.as_bool() {
+                        (p, Known(v)) => p.is_pure() && v == (op == op!("&&")),
+                        _ => false,
+                    },
+                    _ => false,
+                };
+
+                if is_useless_right {
+                    let left = match *left {
+                        Expr::Bin(BinExpr { left, .. }) => left,
+                        _ => unreachable!(),
+                    };
+
+                    return Expr::Bin(BinExpr {
+                        span,
+                        left,
+                        op,
+                        right,
+                    });
+                }
+
+                (left, right)
+            }
         },
         op!("instanceof") => {
             fn is_non_obj(e: &Expr) -> bool {
@@ -572,15 +1153,13 @@ fn fold_bin(
 
                 let rv_int = rv as i32;
                 if rv_int as f64 != rv {
-                    unimplemented!("error reporting: FRACTIONAL_BITWISE_OPERAND")
-                    // report(FRACTIONAL_BITWISE_OPERAND, right.span());
-                    // return n;
+                    // TODO: report(FRACTIONAL_BITWISE_OPERAND, right.span());
+                    return Unknown;
                 }
 
                 if lv.floor() != lv {
-                    unimplemented!("error reporting: FRACTIONAL_BITWISE_OPERAND")
-                    // report(FRACTIONAL_BITWISE_OPERAND, left.span());
-                    // return n;
+                    // TODO: report(FRACTIONAL_BITWISE_OPERAND, left.span());
+                    return Unknown;
                 }
 
                 let bits = js_convert_double_to_bits(lv);
@@ -711,7 +1290,7 @@ fn try_fold_typeof(UnaryExpr { span, op, arg }: UnaryExpr) -> Expr {
     assert_eq!(op, op!("typeof"));
 
     let val = match *arg {
-        Expr::Fn(..) => "function",
+        Expr::Fn(..) | Expr::Arrow(..) | Expr::Class(..) => "function",
         Expr::Lit(Lit::Str { .. }) => "string",
         Expr::Lit(Lit::Num(..)) => "number",
         Expr::Lit(Lit::Bool(..)) => "boolean",
@@ -728,13 +1307,21 @@ fn try_fold_typeof(UnaryExpr { span, op, arg }: UnaryExpr) -> Expr {
             "undefined"
         }
 
-        _ => {
-            return Expr::Unary(UnaryExpr {
-                op: op!("typeof"),
-                arg,
-                span,
-            });
-        }
+        _ => match arg.get_type() {
+            Known(StringType) => "string",
+            // `-a` may be a bigint.
+            Known(NumberType) if arg.as_number().is_known() => "number",
+            Known(BoolType) => "boolean",
+            Known(UndefinedType) => "undefined",
+            Known(NullType) => "object",
+            _ => {
+                return Expr::Unary(UnaryExpr {
+                    op: op!("typeof"),
+                    arg,
+                    span,
+                });
+            }
+        },
     };
 
     Expr::Lit(Lit::Str(Str {
@@ -744,11 +1331,52 @@ fn try_fold_typeof(UnaryExpr { span, op, arg }: UnaryExpr) -> Expr {
     }))
 }
 
+/// Returns true if evaluating the class expression `e` runs no user code.
+///
+/// `may_have_side_effects` is always true for classes.
+fn is_pure_class(e: &Expr) -> bool {
+    let c = match *e {
+        Expr::Class(ClassExpr { ref class, .. }) => class,
+        _ => return false,
+    };
+
+    c.decorators.is_empty()
+        && c.super_class.is_none()
+        && c.body.iter().all(|m| match *m {
+            ClassMember::Constructor(..) | ClassMember::TsIndexSignature(..) => true,
+            ClassMember::Method(ClassMethod {
+                ref key,
+                ref function,
+                ..
+            }) => match *key {
+                PropName::Computed(..) => false,
+                _ => function.decorators.is_empty(),
+            },
+            ClassMember::PrivateMethod(PrivateMethod { ref function, .. }) => {
+                function.decorators.is_empty()
+            }
+            // Static initializers are evaluated with the class.
+            ClassMember::ClassProp(ClassProp {
+                computed,
+                is_static,
+                ref value,
+                ref decorators,
+                ..
+            }) => !computed && (!is_static || value.is_none()) && decorators.is_empty(),
+            ClassMember::PrivateProp(PrivateProp {
+                is_static,
+                ref value,
+                ref decorators,
+                ..
+            }) => (!is_static || value.is_none()) && decorators.is_empty(),
+        })
+}
+
 fn fold_unary(UnaryExpr { span, op, arg }: UnaryExpr) -> Expr {
     let may_have_side_effects = arg.may_have_side_effects();
 
     match op {
-        op!("typeof") if !may_have_side_effects => {
+        op!("typeof") if !may_have_side_effects || is_pure_class(&arg) => {
             return try_fold_typeof(UnaryExpr { span, op, arg });
         }
         op!("!") => match arg.as_bool() {
@@ -779,11 +1407,17 @@ fn fold_unary(UnaryExpr { span, op, arg }: UnaryExpr) -> Expr {
             Expr::Lit(Lit::Num(Number { value: f, .. })) => {
                 return Expr::Lit(Lit::Num(Number { value: -f, span }));
             }
-            _ => {
-
-                // TODO: Report that user is something bad (negating non-number
-                // value)
-            }
+            // `-true` -> `-1`
+            _ => match arg.as_number() {
+                Known(v) if NumEdgeCase::of(-v).is_none() => {
+                    return preserve_effects(
+                        span,
+                        Expr::Lit(Lit::Num(Number { value: -v, span })),
+                        iter::once(arg),
+                    );
+                }
+                _ => {}
+            },
         },
         op!("void") if !may_have_side_effects => {
             return Expr::Unary(UnaryExpr {
@@ -798,13 +1432,14 @@ fn fold_unary(UnaryExpr { span, op, arg }: UnaryExpr) -> Expr {
 
         op!("~") => {
             if let Known(value) = arg.as_number() {
-                if value.fract() == 0.0 {
-                    return Expr::Lit(Lit::Num(Number {
+                return preserve_effects(
+                    span,
+                    Expr::Lit(Lit::Num(Number {
                         span,
-                        value: !(value as u32) as i32 as f64,
-                    }));
-                }
-                // TODO: Report error
+                        value: !to_int32(value) as f64,
+                    })),
+                    iter::once(arg),
+                );
             }
 
             return Expr::Unary(UnaryExpr {
@@ -821,6 +1456,18 @@ fn fold_unary(UnaryExpr { span, op, arg }: UnaryExpr) -> Expr {
 
 /// Try to fold arithmetic binary operators
 fn perform_arithmetic_op(op: BinaryOp, left: &Expr, right: &Expr) -> Value<f64> {
+    let v = perform_arithmetic_op_unchecked(op, left, right)?;
+
+    if can_fold_num(op, left.as_number(), right.as_number(), v) {
+        Known(v)
+    } else {
+        Unknown
+    }
+}
+
+/// Does not check for javascript-specific edge cases. Use
+/// [perform_arithmetic_op] instead.
+fn perform_arithmetic_op_unchecked(op: BinaryOp, left: &Expr, right: &Expr) -> Value<f64> {
     /// Replace only if it becomes shorter
     macro_rules! try_replace {
         ($value:expr) => {{
@@ -903,9 +1550,6 @@ fn perform_arithmetic_op(op: BinaryOp, left: &Expr, right: &Expr) -> Value<f64>
 
         op!("/") => {
             if let (Known(lv), Known(rv)) = (lv, rv) {
-                if rv == 0.0 {
-                    return Unknown;
-                }
                 return try_replace!(lv / rv);
             }
 
@@ -941,12 +1585,7 @@ fn perform_arithmetic_op(op: BinaryOp, left: &Expr, right: &Expr) -> Value<f64>
         op!("&") => return try_replace!(i32, to_int32(lv) & to_int32(rv)),
         op!("|") => return try_replace!(i32, to_int32(lv) | to_int32(rv)),
         op!("^") => return try_replace!(i32, to_int32(lv) ^ to_int32(rv)),
-        op!("%") => {
-            if rv == 0.0 {
-                return Unknown;
-            }
-            return try_replace!(lv % rv);
-        }
+        op!("%") => return try_replace!(lv % rv),
         _ => unreachable!("unknown binary operator: {:?}", op),
     }
 }
@@ -993,7 +1632,9 @@ fn perform_abstract_rel_cmp(
             if lv.contains('\u{000B}') || rv.contains('\u{000B}') {
                 return Unknown;
             } else {
-                return Known(lv < rv);
+                // Strings are compared by their utf-16 code units, which differs from
+                // the byte order of utf-8 for characters outside of the BMP.
+                return Known(lv.encode_utf16().lt(rv.encode_utf16()));
             }
         }
     }
@@ -1034,8 +1675,10 @@ fn perform_abstract_eq_cmp(span: Span, left: &Expr, right: &Expr) -> Value<bool>
 
         (StringType, ObjectType)
         | (NumberType, ObjectType)
+        | (SymbolType, ObjectType)
         | (ObjectType, StringType)
-        | (ObjectType, NumberType) => Unknown,
+        | (ObjectType, NumberType)
+        | (ObjectType, SymbolType) => Unknown,
 
         _ => Known(false),
     }
@@ -1093,6 +1736,22 @@ fn perform_strict_eq_cmp(_span: Span, left: &Expr, right: &Expr) -> Value<bool>
     }
 }
 
+/// Returns true if `l` and `r` are the same identifier or the same literal.
+fn is_same_value(l: &Expr, r: &Expr) -> bool {
+    match (l, r) {
+        (&Expr::Ident(ref l), &Expr::Ident(ref r)) => id(l) == id(r),
+        (&Expr::This(..), &Expr::This(..)) => true,
+        (&Expr::Lit(Lit::Str(ref l)), &Expr::Lit(Lit::Str(ref r))) => l.value == r.value,
+        // `0` and `-0` are different values.
+        (&Expr::Lit(Lit::Num(ref l)), &Expr::Lit(Lit::Num(ref r))) => {
+            l.value.to_bits() == r.value.to_bits()
+        }
+        (&Expr::Lit(Lit::Bool(ref l)), &Expr::Lit(Lit::Bool(ref r))) => l.value == r.value,
+        (&Expr::Lit(Lit::Null(..)), &Expr::Lit(Lit::Null(..))) => true,
+        _ => false,
+    }
+}
+
 /// make a new boolean expression preserving side effects, if any.
 fn make_bool_expr<I>(span: Span, value: bool, orig: I) -> Expr
 where
@@ -0,0 +1,471 @@
+use crate::{pass::Pass, util::*};
+use ast::*;
+use swc_common::{Fold, FoldWith, Span, DUMMY_SP};
+
+#[cfg(test)]
+mod tests;
+
+/// Ported from `PeepholeMinimizeConditions` of google closure compiler.
+///
+/// Rewrites statements into shorter expressions. Unlike [super::dce], this
+/// makes the output less readable, so it's not a part of [super::simplifier].
+pub fn minimize_conditions() -> impl Pass + 'static {
+    MinimizeConditions
+}
+
+struct MinimizeConditions;
+
+impl Fold<Function> for MinimizeConditions {
+    fn fold(&mut self, f: Function) -> Function {
+        let mut f = f.fold_children(self);
+
+        if let Some(ref mut body) = f.body {
+            remove_trailing_return(&mut body.stmts);
+        }
+
+        f
+    }
+}
+
+impl Fold<ArrowExpr> for MinimizeConditions {
+    fn fold(&mut self, f: ArrowExpr) -> ArrowExpr {
+        let mut f = f.fold_children(self);
+
+        if let BlockStmtOrExpr::BlockStmt(ref mut body) = f.body {
+            remove_trailing_return(&mut body.stmts);
+        }
+
+        f
+    }
+}
+
+impl Fold<Vec<Stmt>> for MinimizeConditions {
+    fn fold(&mut self, stmts: Vec<Stmt>) -> Vec<Stmt> {
+        let stmts = stmts.fold_children(self);
+
+        let mut buf: Vec<Stmt> = Vec::with_capacity(stmts.len());
+        for stmt in stmts {
+            buf.push(stmt);
+
+            // `if (a) return b; return c;` -> `return a ? b : c;`
+            //
+            // This is repeated to handle
+            // `if (a) return b; if (c) return d; return e;`
+            while buf.len() >= 2 {
+                let can_merge = match buf[buf.len() - 2] {
+                    Stmt::If(IfStmt {
+                        ref cons,
+                        alt: None,
+                        ..
+                    }) => is_mergeable_jump(cons, &buf[buf.len() - 1]),
+                    _ => false,
+                };
+                let is_void_return = match buf[buf.len() - 2] {
+                    Stmt::If(IfStmt {
+                        ref cons,
+                        alt: None,
+                        ..
+                    }) => is_void_return(cons) && is_void_return(&buf[buf.len() - 1]),
+                    _ => false,
+                };
+                if !can_merge && !is_void_return {
+                    break;
+                }
+
+                let alt = buf.pop().unwrap();
+                let (span, test, cons) = match buf.pop().unwrap() {
+                    Stmt::If(IfStmt {
+                        span, test, cons, ..
+                    }) => (span, test, cons),
+                    _ => unreachable!(),
+                };
+
+                if is_void_return {
+                    // `if (a) return; return;` -> `a; return;`
+                    buf.push(Stmt::Expr(ExprStmt { span, expr: test }));
+                    buf.push(alt);
+                    break;
+                }
+
+                buf.push(merge_jumps(span, test, *cons, alt));
+            }
+        }
+
+        buf
+    }
+}
+
+impl Fold<Expr> for MinimizeConditions {
+    fn fold(&mut self, e: Expr) -> Expr {
+        let e = e.fold_children(self);
+
+        match e {
+            Expr::Unary(UnaryExpr {
+                span,
+                op: op!("!"),
+                arg,
+            }) => match *simplify_bool(arg) {
+                // `!(a == b)` -> `a != b`
+                Expr::Bin(bin) => match negate_bin_op(&bin) {
+                    Some(op) => Expr::Bin(BinExpr { op, ..bin }),
+                    None => Expr::Unary(UnaryExpr {
+                        span,
+                        op: op!("!"),
+                        arg: box Expr::Bin(bin),
+                    }),
+                },
+                arg => Expr::Unary(UnaryExpr {
+                    span,
+                    op: op!("!"),
+                    arg: box arg,
+                }),
+            },
+
+            Expr::Cond(CondExpr {
+                span,
+                test,
+                cons,
+                alt,
+            }) => match invert(simplify_bool(test)) {
+                // `!a ? b : c` -> `a ? c : b`
+                Ok(test) => Expr::Cond(CondExpr {
+                    span,
+                    test,
+                    cons: alt,
+                    alt: cons,
+                }),
+                Err(test) => Expr::Cond(CondExpr {
+                    span,
+                    test,
+                    cons,
+                    alt,
+                }),
+            },
+
+            _ => e,
+        }
+    }
+}
+
+impl Fold<Stmt> for MinimizeConditions {
+    fn fold(&mut self, stmt: Stmt) -> Stmt {
+        let stmt = stmt.fold_children(self);
+
+        let stmt = match stmt {
+            Stmt::If(IfStmt {
+                span,
+                test,
+                cons,
+                alt: Some(alt),
+            }) => match invert(simplify_bool(test)) {
+                // `if (!a) b; else c;` -> `if (a) c; else b;`
+                Ok(test) => Stmt::If(IfStmt {
+                    span,
+                    test,
+                    cons: alt,
+                    alt: Some(cons),
+                }),
+                Err(test) => Stmt::If(IfStmt {
+                    span,
+                    test,
+                    cons,
+                    alt: Some(alt),
+                }),
+            },
+            Stmt::If(s) => Stmt::If(IfStmt {
+                test: simplify_bool(s.test),
+                ..s
+            }),
+            Stmt::While(s) => Stmt::While(WhileStmt {
+                test: simplify_bool(s.test),
+                ..s
+            }),
+            Stmt::DoWhile(s) => Stmt::DoWhile(DoWhileStmt {
+                test: simplify_bool(s.test),
+                ..s
+            }),
+            Stmt::For(s) => Stmt::For(ForStmt {
+                test: s.test.map(simplify_bool),
+                ..s
+            }),
+            _ => stmt,
+        };
+
+        match stmt {
+            // `if (a) b(); else c();` -> `a ? b() : c();`
+            Stmt::If(IfStmt {
+                span,
+                test,
+                cons,
+                alt: Some(alt),
+            }) => {
+                // `if (a) return b; else return c;` -> `return a ? b : c;`
+                if is_mergeable_jump(&cons, &alt) {
+                    return merge_jumps(span, test, *cons, *alt);
+                }
+
+                if as_expr(&cons).is_none() || as_expr(&alt).is_none() {
+                    return Stmt::If(IfStmt {
+                        span,
+                        test,
+                        cons,
+                        alt: Some(alt),
+                    });
+                }
+
+                let (cons, alt) = (into_expr(*cons), into_expr(*alt));
+
+                let expr = match (*cons, *alt) {
+                    // `if (a) x = 1; else x = 2;` -> `x = a ? 1 : 2;`
+                    (Expr::Assign(cons), Expr::Assign(alt))
+                        if cons.op == alt.op && is_same_ident(&cons.left, &alt.left) =>
+                    {
+                        Expr::Assign(AssignExpr {
+                            right: box Expr::Cond(CondExpr {
+                                span: DUMMY_SP,
+                                test,
+                                cons: cons.right,
+                                alt: alt.right,
+                            }),
+                            ..cons
+                        })
+                    }
+
+                    (cons, alt) => Expr::Cond(CondExpr {
+                        span,
+                        test,
+                        cons: box cons,
+                        alt: box alt,
+                    }),
+                };
+
+                Stmt::Expr(ExprStmt {
+                    span,
+                    expr: box expr,
+                })
+            }
+
+            _ => stmt,
+        }
+    }
+}
+
+fn unwrap_paren(e: Box<Expr>) -> Box<Expr> {
+    match *e {
+        Expr::Paren(ParenExpr { expr, .. }) => unwrap_paren(expr),
+        _ => e,
+    }
+}
+
+/// Simplifies `e`, whose value is only used as a boolean.
+fn simplify_bool(e: Box<Expr>) -> Box<Expr> {
+    match *unwrap_paren(e) {
+        Expr::Unary(UnaryExpr {
+            span,
+            op: op!("!"),
+            arg,
+        }) => match *simplify_bool(arg) {
+            // `!!a` -> `a`
+            Expr::Unary(UnaryExpr {
+                op: op!("!"), arg, ..
+            }) => arg,
+
+            // `!(!a && !b)` -> `a || b`
+            arg => match invert(box arg) {
+                Ok(arg) => arg,
+                Err(arg) => box Expr::Unary(UnaryExpr {
+                    span,
+                    op: op!("!"),
+                    arg,
+                }),
+            },
+        },
+
+        Expr::Bin(BinExpr {
+            span,
+            op,
+            left,
+            right,
+        }) if op == op!("&&") || op == op!("||") => {
+            let left = simplify_bool(left);
+            let right = simplify_bool(right);
+
+            match (op, right.as_pure_bool()) {
+                // `a && true` -> `a`
+                (op!("&&"), Known(true)) | (op!("||"), Known(false)) => left,
+                _ => box Expr::Bin(BinExpr {
+                    span,
+                    op,
+                    left,
+                    right,
+                }),
+            }
+        }
+
+        e => box e,
+    }
+}
+
+/// Returns `a` if `e` is equivalent to `!a` in a boolean context.
+fn invert(e: Box<Expr>) -> Result<Box<Expr>, Box<Expr>> {
+    match *e {
+        Expr::Unary(UnaryExpr {
+            op: op!("!"), arg, ..
+        }) => Ok(arg),
+
+        // `!a && !b` -> `!(a || b)`
+        Expr::Bin(BinExpr {
+            span,
+            op,
+            left:
+                box Expr::Unary(UnaryExpr {
+                    op: op!("!"),
+                    arg: left,
+                    ..
+                }),
+            right:
+                box Expr::Unary(UnaryExpr {
+                    op: op!("!"),
+                    arg: right,
+                    ..
+                }),
+        }) if op == op!("&&") || op == op!("||") => Ok(box Expr::Bin(BinExpr {
+            span,
+            op: if op == op!("&&") {
+                op!("||")
+            } else {
+                op!("&&")
+            },
+            left,
+            right,
+        })),
+
+        _ => Err(e),
+    }
+}
+
+/// Returns the operator of `!(e)`, if it can be expressed with a binary
+/// expression.
+fn negate_bin_op(e: &BinExpr) -> Option<BinaryOp> {
+    // `!(a < b)` is not `a >= b` if an operand is converted to `NaN`, but
+    // strings are always comparable.
+    let is_comparable =
+        || e.left.get_type() == Known(StringType) && e.right.get_type() == Known(StringType);
+
+    Some(match e.op {
+        op!("==") => op!("!="),
+        op!("!=") => op!("=="),
+        op!("===") => op!("!=="),
+        op!("!==") => op!("==="),
+        op!("<") if is_comparable() => op!(">="),
+        op!("<=") if is_comparable() => op!(">"),
+        op!(">") if is_comparable() => op!("<="),
+        op!(">=") if is_comparable() => op!("<"),
+        _ => return None,
+    })
+}
+
+/// Removes `return;` at the end of a function body.
+fn remove_trailing_return(stmts: &mut Vec<Stmt>) {
+    if let Some(Stmt::Return(ReturnStmt { arg: None, .. })) = stmts.last() {
+        stmts.pop();
+    }
+}
+
+/// Unwraps blocks with only one statement.
+fn single_stmt(s: &Stmt) -> &Stmt {
+    match *s {
+        Stmt::Block(BlockStmt { ref stmts, .. }) if stmts.len() == 1 => single_stmt(&stmts[0]),
+        _ => s,
+    }
+}
+
+/// Unwraps blocks with only one statement.
+fn into_single_stmt(s: Stmt) -> Stmt {
+    match s {
+        Stmt::Block(BlockStmt { mut stmts, .. }) if stmts.len() == 1 => {
+            into_single_stmt(stmts.pop().unwrap())
+        }
+        _ => s,
+    }
+}
+
+/// Returns the expression of `s` if `s` is an expression statement, possibly
+/// wrapped in a block.
+fn as_expr(s: &Stmt) -> Option<&Expr> {
+    match *single_stmt(s) {
+        Stmt::Expr(ExprStmt { ref expr, .. }) => Some(expr),
+        _ => None,
+    }
+}
+
+/// Panics if [as_expr] returns [None] for `s`.
+fn into_expr(s: Stmt) -> Box<Expr> {
+    match into_single_stmt(s) {
+        Stmt::Expr(ExprStmt { expr, .. }) => expr,
+        s => unreachable!("into_expr: {:?}", s),
+    }
+}
+
+fn is_void_return(s: &Stmt) -> bool {
+    match *single_stmt(s) {
+        Stmt::Return(ReturnStmt { arg: None, .. }) => true,
+        _ => false,
+    }
+}
+
+/// Returns true if `cons` and `alt` can be merged by [merge_jumps].
+fn is_mergeable_jump(cons: &Stmt, alt: &Stmt) -> bool {
+    match (single_stmt(cons), single_stmt(alt)) {
+        (&Stmt::Throw(..), &Stmt::Throw(..)) => true,
+        (
+            &Stmt::Return(ReturnStmt { arg: ref l, .. }),
+            &Stmt::Return(ReturnStmt { arg: ref r, .. }),
+        ) => l.is_some() || r.is_some(),
+        _ => false,
+    }
+}
+
+/// `return a` and `return b` -> `return test ? a : b`
+///
+/// Panics if [is_mergeable_jump] returns false for `cons` and `alt`.
+fn merge_jumps(span: Span, test: Box<Expr>, cons: Stmt, alt: Stmt) -> Stmt {
+    match (into_single_stmt(cons), into_single_stmt(alt)) {
+        (Stmt::Throw(cons), Stmt::Throw(alt)) => Stmt::Throw(ThrowStmt {
+            span,
+            arg: box Expr::Cond(CondExpr {
+                span: DUMMY_SP,
+                test,
+                cons: cons.arg,
+                alt: alt.arg,
+            }),
+        }),
+        (Stmt::Return(cons), Stmt::Return(alt)) => Stmt::Return(ReturnStmt {
+            span,
+            arg: Some(box Expr::Cond(CondExpr {
+                span: DUMMY_SP,
+                test,
+                cons: cons.arg.unwrap_or_else(|| undefined(cons.span)),
+                alt: alt.arg.unwrap_or_else(|| undefined(alt.span)),
+            })),
+        }),
+        (cons, alt) => unreachable!("merge_jumps: {:?} {:?}", cons, alt),
+    }
+}
+
+/// Returns true if both of `l` and `r` are the same identifier.
+fn is_same_ident(l: &PatOrExpr, r: &PatOrExpr) -> bool {
+    fn as_ident(e: &PatOrExpr) -> Option<&Ident> {
+        match *e {
+            PatOrExpr::Pat(box Pat::Ident(ref i)) | PatOrExpr::Expr(box Expr::Ident(ref i)) => {
+                Some(i)
+            }
+            _ => None,
+        }
+    }
+
+    match (as_ident(l), as_ident(r)) {
+        (Some(l), Some(r)) => id(l) == id(r),
+        _ => false,
+    }
+}
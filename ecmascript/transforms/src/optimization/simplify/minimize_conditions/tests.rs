@@ -0,0 +1,105 @@
+use super::minimize_conditions;
+
+fn test(src: &str, expected: &str) {
+    test_transform!(
+        ::swc_ecma_parser::Syntax::default(),
+        |_| minimize_conditions(),
+        src,
+        expected,
+        true
+    )
+}
+
+fn test_same(s: &str) {
+    test(s, s)
+}
+
+#[test]
+fn if_else_to_cond() {
+    test("if (a) b(); else c();", "a ? b() : c();");
+    test("if (a) { b(); } else { c(); }", "a ? b() : c();");
+    test(
+        "if (a) { b(); } else if (c) d(); else e();",
+        "a ? b() : c ? d() : e();",
+    );
+    test_same("if (a) b(); else { c(); d(); }");
+    test_same("if (a) return; else c();");
+    test_same("if (a) b();");
+}
+
+#[test]
+fn if_else_to_assign() {
+    test("if (a) x = 1; else x = 2;", "x = a ? 1 : 2;");
+    test("if (a) x += 1; else x += 2;", "x += a ? 1 : 2;");
+    test("if (a) x = 1; else x += 2;", "a ? x = 1 : x += 2;");
+    test("if (a) x = 1; else y = 2;", "a ? x = 1 : y = 2;");
+    test("if (a) x.y = 1; else x.y = 2;", "a ? x.y = 1 : x.y = 2;");
+}
+
+#[test]
+fn merge_returns() {
+    test(
+        "function f() { if (a) return b; else return c; }",
+        "function f() { return a ? b : c; }",
+    );
+    test(
+        "function f() { if (a) { return b; } return c; }",
+        "function f() { return a ? b : c; }",
+    );
+    test(
+        "function f() { if (a) return b; if (c) return d; return e; }",
+        "function f() { return a ? b : c ? d : e; }",
+    );
+    test(
+        "function f() { foo(); if (a) return; return c; }",
+        "function f() { foo(); return a ? void 0 : c; }",
+    );
+    test(
+        "function f() { if (a) throw b; throw c; }",
+        "function f() { throw a ? b : c; }",
+    );
+    test_same("function f() { if (a) return b; throw c; }");
+    test_same("function f() { if (a) return b; foo(); }");
+}
+
+#[test]
+fn remove_trailing_return() {
+    test("function f() { foo(); return; }", "function f() { foo(); }");
+    test("() => { foo(); return; }", "() => { foo(); }");
+    test(
+        "function f() { if (a) return; return; }",
+        "function f() { a; }",
+    );
+    test_same("function f() { for (;;) { foo(); return; } }");
+}
+
+#[test]
+fn negation() {
+    test("!(a == b)", "a != b");
+    test("!(a !== b)", "a === b");
+    test("!(a < b)", "!(a < b)");
+    test("!('' + a < 'b')", "'' + a >= 'b'");
+    test_same("!(a + b)");
+    test("!a ? b : c", "a ? c : b");
+    test("if (!a) b(); else c();", "a ? c() : b();");
+    test(
+        "if (!a) { b(); foo(); } else c();",
+        "if (a) c(); else { b(); foo(); }",
+    );
+    test_same("if (!a) b();");
+}
+
+#[test]
+fn boolean_algebra() {
+    test("if (!!a) b();", "if (a) b();");
+    test("!!!a", "!a");
+    test_same("x = !!a");
+    test("if (!(!a && !b)) c();", "if (a || b) c();");
+    test("if (!a && !b) c(); else d();", "a || b ? d() : c();");
+    test("!a || !b ? c : d", "a && b ? d : c");
+    test_same("x = !a && !b");
+    test("while (a && true) b();", "while (a) b();");
+    test("for (; a || false;) b();", "for (; a;) b();");
+    test("do b(); while (!!a || 0);", "do b(); while (a);");
+    test_same("x = a && true");
+}
@@ -7,11 +7,13 @@ pub use self::{
     factory::ExprFactory,
     ident::{id, Id},
     value::{
+        can_fold_num, num_to_str, NumEdgeCase,
         Type::{
             self, Bool as BoolType, Null as NullType, Num as NumberType, Obj as ObjectType,
             Str as StringType, Symbol as SymbolType, Undefined as UndefinedType,
         },
         Value::{self, Known, Unknown},
+        MAX_SAFE_INTEGER,
     },
     Purity::{MayBeImpure, Pure},
 };
@@ -26,8 +28,9 @@ use std::{
 };
 use swc_atoms::{js_word, JsWord};
 use swc_common::{
-    comments::Comments, errors::Handler, Fold, FoldWith, Mark, Span, Spanned, Visit, VisitWith,
-    DUMMY_SP,
+    comments::{CommentKind, Comments},
+    errors::Handler,
+    Fold, FoldWith, Mark, Span, Spanned, Visit, VisitWith, DUMMY_SP,
 };
 use unicode_xid::UnicodeXID;
 
@@ -477,6 +480,21 @@ pub trait ExprExt {
                 v
             }
 
+            // `a && false` is always falsy and `a || true` is always truthy, even if
+            // the value of `a` is not known.
+            Expr::Bin(BinExpr {
+                ref left,
+                op: op!("&&"),
+                ref right,
+                ..
+            }) => left.as_bool().1.and(right.as_bool().1),
+            Expr::Bin(BinExpr {
+                ref left,
+                op: op!("||"),
+                ref right,
+                ..
+            }) => left.as_bool().1.or(right.as_bool().1),
+
             Expr::Fn(..) | Expr::Class(..) | Expr::New(..) | Expr::Array(..) | Expr::Object(..) => {
                 Known(true)
             }
@@ -584,16 +602,30 @@ pub trait ExprExt {
         match *expr {
             Expr::Lit(ref l) => match *l {
                 Lit::Str(Str { ref value, .. }) => Known(Cow::Borrowed(value)),
-                Lit::Num(ref n) => Known(format!("{}", n).into()),
+                Lit::Num(Number { value, .. }) => num_to_str(value),
                 Lit::Bool(Bool { value: true, .. }) => Known(Cow::Borrowed("true")),
                 Lit::Bool(Bool { value: false, .. }) => Known(Cow::Borrowed("false")),
                 Lit::Null(..) => Known(Cow::Borrowed("null")),
                 _ => Unknown,
             },
-            Expr::Tpl(_) => {
-                // TODO:
+            Expr::Tpl(Tpl {
+                ref exprs,
+                ref quasis,
+                ..
+            }) => {
                 // Only convert a template literal if all its expressions can be converted.
-                unimplemented!("TplLit.as_string()")
+                let mut buf = String::new();
+                for (i, quasi) in quasis.iter().enumerate() {
+                    match quasi.cooked {
+                        Some(ref cooked) => buf.push_str(&cooked.value),
+                        None => return Unknown,
+                    }
+                    if let Some(expr) = exprs.get(i) {
+                        buf.push_str(&expr.as_string()?);
+                    }
+                }
+
+                Known(Cow::Owned(buf))
             }
             Expr::Ident(Ident { ref sym, .. }) => match *sym {
                 js_word!("undefined") | js_word!("Infinity") | js_word!("NaN") => {
@@ -825,6 +857,14 @@ pub trait ExprExt {
                 ..
             }) if obj.is_ident_ref_to(js_word!("Math")) => true,
 
+            // String.raw
+            Expr::Member(MemberExpr {
+                obj: ExprOrSuper::Expr(ref obj),
+                prop: box Expr::Ident(Ident { ref sym, .. }),
+                computed: false,
+                ..
+            }) if obj.is_ident_ref_to(js_word!("String")) && &**sym == "raw" => true,
+
             Expr::Fn(FnExpr {
                 function:
                     Function {
@@ -868,8 +908,9 @@ pub trait ExprExt {
                 ..
             }) => left.may_have_side_effects() || right.may_have_side_effects(),
 
+            Expr::Tpl(Tpl { ref exprs, .. }) => exprs.iter().any(|e| e.may_have_side_effects()),
+
             //TODO
-            Expr::Tpl(_) => true,
             Expr::TaggedTpl(_) => true,
             Expr::MetaProp(_) => true,
 
@@ -879,6 +920,12 @@ pub trait ExprExt {
             | Expr::Update(_)
             | Expr::Assign(_) => true,
 
+            // `/*#__PURE__*/ new Foo(a)` only evaluates the arguments.
+            Expr::New(NewExpr { span, ref args, .. }) if has_annotation(span, "__PURE__") => args
+                .iter()
+                .flatten()
+                .any(|arg| arg.expr.may_have_side_effects()),
+
             // TODO
             Expr::New(_) => true,
 
@@ -886,6 +933,9 @@ pub trait ExprExt {
                 callee: ExprOrSuper::Expr(ref callee),
                 ..
             }) if callee.is_pure_callee() => false,
+            Expr::Call(CallExpr { span, ref args, .. }) if has_annotation(span, "__PURE__") => {
+                args.iter().any(|arg| arg.expr.may_have_side_effects())
+            }
             Expr::Call(_) => true,
 
             Expr::Seq(SeqExpr { ref exprs, .. }) => exprs.iter().any(|e| e.may_have_side_effects()),
@@ -1533,9 +1583,120 @@ impl<'a> UsageFinder<'a> {
     }
 }
 
+/// Returns true if `node` declares or assigns to `sym`, excluding nested
+/// functions.
+///
+/// Block scoped declarations are treated as if they are declared in the
+/// function.
+pub fn declares<T>(node: &T, sym: &JsWord) -> bool
+where
+    T: VisitWith<DeclFinder>,
+{
+    let mut v = DeclFinder {
+        sym: sym.clone(),
+        found: false,
+    };
+    node.visit_with(&mut v);
+    v.found
+}
+
+pub struct DeclFinder {
+    sym: JsWord,
+    found: bool,
+}
+
+impl Visit<Pat> for DeclFinder {
+    fn visit(&mut self, p: &Pat) {
+        match *p {
+            Pat::Ident(ref i) if i.sym == self.sym => self.found = true,
+            _ => p.visit_children(self),
+        }
+    }
+}
+
+impl Visit<PatOrExpr> for DeclFinder {
+    fn visit(&mut self, p: &PatOrExpr) {
+        match *p {
+            PatOrExpr::Expr(box Expr::Ident(ref i)) if i.sym == self.sym => self.found = true,
+            _ => p.visit_children(self),
+        }
+    }
+}
+
+impl Visit<UpdateExpr> for DeclFinder {
+    fn visit(&mut self, e: &UpdateExpr) {
+        match *e.arg {
+            Expr::Ident(ref i) if i.sym == self.sym => self.found = true,
+            _ => e.visit_children(self),
+        }
+    }
+}
+
+impl Visit<FnDecl> for DeclFinder {
+    fn visit(&mut self, f: &FnDecl) {
+        if f.ident.sym == self.sym {
+            self.found = true;
+        }
+    }
+}
+
+impl Visit<ClassDecl> for DeclFinder {
+    fn visit(&mut self, c: &ClassDecl) {
+        if c.ident.sym == self.sym {
+            self.found = true;
+        }
+        c.class.visit_with(self);
+    }
+}
+
+impl Visit<ImportDecl> for DeclFinder {
+    fn visit(&mut self, d: &ImportDecl) {
+        let found = d.specifiers.iter().any(|s| match *s {
+            ImportSpecifier::Specific(ImportSpecific { ref local, .. })
+            | ImportSpecifier::Default(ImportDefault { ref local, .. })
+            | ImportSpecifier::Namespace(ImportStarAs { ref local, .. }) => local.sym == self.sym,
+        });
+        if found {
+            self.found = true;
+        }
+    }
+}
+
+/// Nested functions are not searched.
+impl Visit<Function> for DeclFinder {
+    fn visit(&mut self, _: &Function) {}
+}
+
+/// Nested functions are not searched.
+impl Visit<ArrowExpr> for DeclFinder {
+    fn visit(&mut self, _: &ArrowExpr) {}
+}
+
 scoped_thread_local!(pub static HANDLER: Handler);
 scoped_thread_local!(pub static COMMENTS: Comments);
 
+/// Returns true if `span` has a leading annotation comment like
+/// `/*#__PURE__*/` or `/*@__PURE__*/`, where `name` is `__PURE__`.
+///
+/// Returns false if [COMMENTS] is not set.
+pub fn has_annotation(span: Span, name: &str) -> bool {
+    if span.is_dummy() || !COMMENTS.is_set() {
+        return false;
+    }
+
+    COMMENTS.with(|c| match c.leading_comments(span.lo()) {
+        Some(cmts) => cmts.iter().any(|cmt| {
+            if cmt.kind != CommentKind::Block {
+                return false;
+            }
+
+            let text = cmt.text.trim();
+            (text.starts_with('#') || text.starts_with('@')) && &text[1..] == name
+        }),
+        None => false,
+    })
+}
+
 /// make a new expression which evaluates `val` preserving side effects, if any.
 pub fn preserve_effects<I>(span: Span, val: Expr, exprs: I) -> Expr
 where
@@ -1655,7 +1816,7 @@ where
             }
 
             Expr::TaggedTpl { .. } => unimplemented!("add_effects for tagged template literal"),
-            Expr::Tpl { .. } => unimplemented!("add_effects for template literal"),
+            Expr::Tpl(Tpl { exprs, .. }) => exprs.into_iter().for_each(|e| add_effects(v, e)),
             Expr::Class(ClassExpr { .. }) => unimplemented!("add_effects for class expression"),
 
             Expr::JSXMember(..)
@@ -1700,3 +1861,17 @@ pub fn prop_name_eq(p: &PropName, key: &str) -> bool {
         },
     }
 }
+
+/// Methods of `String.prototype` which don't have side effects and can be
+/// evaluated at compile time if the receiver and the arguments are literals.
+pub const PURE_STRING_METHODS: &[&str] = &[
+    "charAt",
+    "charCodeAt",
+    "indexOf",
+    "lastIndexOf",
+    "slice",
+    "split",
+    "substring",
+    "toLowerCase",
+    "toUpperCase",
+];
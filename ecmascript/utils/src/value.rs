@@ -1,5 +1,9 @@
 use self::Value::{Known, Unknown};
-use std::ops::{Not, Try};
+use ast::*;
+use std::{
+    borrow::Cow,
+    ops::{Not, Try},
+};
 
 /// Runtime value.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -103,3 +107,104 @@ impl Not for Value<bool> {
         }
     }
 }
+
+/// `Number.MAX_SAFE_INTEGER`
+pub const MAX_SAFE_INTEGER: f64 = 9_007_199_254_740_991.0;
+
+/// Numbers which need care while folding.
+///
+/// Javascript numbers are IEEE 754 doubles like `f64`, so arithmetic itself
+/// matches. These are the values where javascript and rust disagree, or
+/// where a folded literal would hide a rounding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NumEdgeCase {
+    /// `NaN`
+    NaN,
+    /// `Infinity` and `-Infinity`. Rust prints them as `inf`.
+    Infinite,
+    /// `-0`. `String(-0)` is `"0"`, while rust prints `-0`.
+    NegZero,
+    /// `ToString` uses exponential notation (`1e+21`, `1e-7`) for these, while
+    /// rust never does.
+    Exponential,
+    /// An integer larger than [MAX_SAFE_INTEGER], which is possibly rounded.
+    ///
+    /// Integers larger than `1e21` are [NumEdgeCase::Exponential].
+    UnsafeInteger,
+}
+
+impl NumEdgeCase {
+    pub fn of(v: f64) -> Option<Self> {
+        if v.is_nan() {
+            Some(NumEdgeCase::NaN)
+        } else if v.is_infinite() {
+            Some(NumEdgeCase::Infinite)
+        } else if v == 0.0 {
+            if v.is_sign_negative() {
+                Some(NumEdgeCase::NegZero)
+            } else {
+                None
+            }
+        } else if v.abs() >= 1e21 || v.abs() < 1e-6 {
+            Some(NumEdgeCase::Exponential)
+        } else if v.abs() > MAX_SAFE_INTEGER {
+            Some(NumEdgeCase::UnsafeInteger)
+        } else {
+            None
+        }
+    }
+}
+
+/// `ToString` of a number.
+///
+/// Returns [Unknown] if javascript would use exponential notation.
+pub fn num_to_str(v: f64) -> Value<Cow<'static, str>> {
+    match NumEdgeCase::of(v) {
+        Some(NumEdgeCase::NaN) => Known(Cow::Borrowed("NaN")),
+        Some(NumEdgeCase::Infinite) => Known(Cow::Borrowed(if v.is_sign_positive() {
+            "Infinity"
+        } else {
+            "-Infinity"
+        })),
+        Some(NumEdgeCase::NegZero) => Known(Cow::Borrowed("0")),
+        Some(NumEdgeCase::Exponential) => Unknown,
+        Some(NumEdgeCase::UnsafeInteger) | None => Known(Cow::Owned(format!("{}", v))),
+    }
+}
+
+/// Returns true if `l op r` can be replaced with `result`, which is computed
+/// using `f64`.
+///
+/// An operand is [Unknown] if it's not a constant, like `x` in `x ** 0`.
+///
+/// | Case            | Javascript         | Rust             | Folded |
+/// |-----------------|--------------------|------------------|--------|
+/// | `x / 0`         | `NaN` / `Infinity` | same             | no     |
+/// | `x % 0`         | `NaN`              | same             | no     |
+/// | `1 ** NaN`      | `NaN`              | `1`              | no     |
+/// | `(-1) ** ±Infinity` | `NaN`          | `1`              | no     |
+/// | `2 ** 60`       | rounded            | rounded          | no     |
+/// | `-1 * 0`        | `-0`               | `-0`             | yes    |
+/// | `NaN * 1`       | `NaN`              | `NaN`            | yes    |
+/// | `1e308 * 10`    | `Infinity`         | `inf`            | yes    |
+pub fn can_fold_num(op: BinaryOp, l: Value<f64>, r: Value<f64>, result: f64) -> bool {
+    match (op, l, r) {
+        // `0 / 0` is `NaN`, and the others depend on the sign of zero.
+        (op!("/"), _, Known(r)) | (op!("%"), _, Known(r)) if r == 0.0 => return false,
+        // `powf` returns 1 for these.
+        (op!("**"), Known(l), Known(r)) if l.abs() == 1.0 && !r.is_finite() => return false,
+        _ => {}
+    }
+
+    match NumEdgeCase::of(result) {
+        None
+        | Some(NumEdgeCase::NaN)
+        | Some(NumEdgeCase::Infinite)
+        | Some(NumEdgeCase::NegZero) => true,
+        // Small numbers are printed without an exponent, which is still valid
+        // javascript.
+        Some(NumEdgeCase::Exponential) if result.abs() < 1.0 => true,
+        // The result is possibly rounded, so we keep the original expression.
+        Some(NumEdgeCase::Exponential) | Some(NumEdgeCase::UnsafeInteger) => false,
+    }
+}
@@ -10,7 +10,7 @@ use ecmascript::{
     preset_env,
     transforms::{
         const_modules, modules,
-        optimization::{simplifier, InlineGlobals, JsonParse},
+        optimization::{simplifier, CompressLiterals, InlineGlobals, JsonParse},
         pass::{noop, Optional, Pass},
         proposals::{class_properties, decorators, export, nullish_coalescing, optional_chaining},
         react, resolver, typescript,
@@ -153,6 +153,7 @@ impl Options {
         }
         let optimizer = transform.optimizer;
         let enable_optimizer = optimizer.is_some();
+        let compress = optimizer.as_ref().map(|o| o.compress).unwrap_or(false);
 
         let const_modules = {
             let enabled = transform.const_modules.is_some();
@@ -207,6 +208,7 @@ impl Options {
                 syntax.export_default_from() || syntax.export_namespace_from()
             ),
             Optional::new(simplifier(), enable_optimizer),
+            Optional::new(CompressLiterals, compress),
             json_parse_pass
         );
 
@@ -508,6 +510,10 @@ pub struct OptimizerConfig {
 
     #[serde(default)]
     pub jsonify: Option<JsonifyOption>,
+
+    /// Shortens literals, e.g. `true` to `!0`.
+    #[serde(default)]
+    pub compress: bool,
 }
 
 #[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
@@ -689,7 +695,8 @@ impl Merge for TransformConfig {
 
 impl Merge for OptimizerConfig {
     fn merge(&mut self, from: &Self) {
-        self.globals.merge(&from.globals)
+        self.globals.merge(&from.globals);
+        self.compress.merge(&from.compress);
     }
 }
 